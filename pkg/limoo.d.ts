@@ -1,7 +0,0 @@
-/* tslint:disable */
-/* eslint-disable */
-/**
-* @param {string} input
-* @returns {string}
-*/
-export function limoo_eval(input: string): string;
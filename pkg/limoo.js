@@ -1,2 +0,0 @@
-import * as wasm from "./limoo_bg.wasm";
-export * from "./limoo_bg.js";
\ No newline at end of file
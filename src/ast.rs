@@ -1,6 +1,9 @@
 use std::fmt;
 
-use crate::token::Token;
+use crate::{
+    bigint::BigInt,
+    token::{Span, Token},
+};
 
 #[derive(Debug)]
 pub enum Node {
@@ -20,18 +23,42 @@ impl fmt::Display for Node {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Statement {
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+impl Statement {
+    pub fn new(kind: StatementKind, span: Span) -> Self {
+        Statement { kind, span }
+    }
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatementKind {
     Let(String, Expression),
     Return(Expression),
+    Break,
+    Continue,
+    Throw(Expression),
     Expr(Expression),
 }
 
-impl fmt::Display for Statement {
+impl fmt::Display for StatementKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Statement::Let(id, expr) => write!(f, "let {} = {};", id, expr),
-            Statement::Return(expr) => write!(f, "return {};", expr),
-            Statement::Expr(expr) => write!(f, "{}", expr),
+            StatementKind::Let(id, expr) => write!(f, "let {} = {};", id, expr),
+            StatementKind::Return(expr) => write!(f, "return {};", expr),
+            StatementKind::Break => write!(f, "break;"),
+            StatementKind::Continue => write!(f, "continue;"),
+            StatementKind::Throw(expr) => write!(f, "throw {};", expr),
+            StatementKind::Expr(expr) => write!(f, "{}", expr),
         }
     }
 }
@@ -39,29 +66,60 @@ impl fmt::Display for Statement {
 pub type BlockStatement = Vec<Statement>;
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Expression {
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Expression { kind, span }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionKind {
     Ident(String),
     Lit(Literal),
     Prefix(Token, Box<Expression>),
     Infix(Box<Expression>, Token, Box<Expression>),
     If(Box<Expression>, BlockStatement, Option<BlockStatement>),
     While(Box<Expression>, BlockStatement),
-    Function(Vec<String>, BlockStatement),
+    For(
+        Option<Box<Statement>>,
+        Option<Box<Expression>>,
+        Option<Box<Expression>>,
+        BlockStatement,
+    ),
+    ForIn(String, Box<Expression>, BlockStatement),
+    Try(BlockStatement, Option<(String, BlockStatement)>, Option<BlockStatement>),
+    Function(Vec<String>, BlockStatement, Option<String>),
     FunctionCall(Box<Expression>, Vec<Expression>),
-    Assign(String, Token, Box<Expression>),
+    Assign(Box<Expression>, Token, Box<Expression>),
+    PrefixUpdate(Token, Box<Expression>),
+    PostfixUpdate(Box<Expression>, Token),
     Index(Box<Expression>, Box<Expression>),
+    // String literal parts are text, anything else is an embedded `${...}` expression.
+    Interpolation(Vec<Expression>),
+    Slice(Box<Expression>, Option<Box<Expression>>, Option<Box<Expression>>),
 }
 
-impl fmt::Display for Expression {
+impl fmt::Display for ExpressionKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Expression::Ident(id) => write!(f, "{}", id),
-            Expression::Lit(lit) => write!(f, "{}", lit),
-            Expression::Prefix(prefix, expr) => write!(f, "({}{})", prefix, expr),
-            Expression::Infix(left_expression, operator, right_expression) => {
+            ExpressionKind::Ident(id) => write!(f, "{}", id),
+            ExpressionKind::Lit(lit) => write!(f, "{}", lit),
+            ExpressionKind::Prefix(prefix, expr) => write!(f, "({}{})", prefix, expr),
+            ExpressionKind::Infix(left_expression, operator, right_expression) => {
                 write!(f, "({} {} {})", left_expression, operator, right_expression)
             }
-            Expression::If(condition, then_block, else_block) => {
+            ExpressionKind::If(condition, then_block, else_block) => {
                 if let Some(else_block) = else_block {
                     write!(
                         f,
@@ -74,40 +132,108 @@ impl fmt::Display for Expression {
                     write!(f, "if {} {{ {} }}", condition, format_statements(then_block))
                 }
             }
-            Expression::While(condition, block) => {
+            ExpressionKind::While(condition, block) => {
                 write!(f, "while {} {{ {} }}", condition, format_statements(block))
             }
-            Expression::Function(params, _block) => {
+            ExpressionKind::For(init, condition, update, block) => {
+                let init = init.as_ref().map(|init| init.to_string()).unwrap_or_default();
+                let condition = condition
+                    .as_ref()
+                    .map(|condition| condition.to_string())
+                    .unwrap_or_default();
+                let update = update.as_ref().map(|update| update.to_string()).unwrap_or_default();
+
+                write!(
+                    f,
+                    "for ({}; {}; {}) {{ {} }}",
+                    init.trim_end_matches(';'),
+                    condition,
+                    update,
+                    format_statements(block)
+                )
+            }
+            ExpressionKind::ForIn(variable, iterable, block) => {
+                write!(
+                    f,
+                    "for ({} in {}) {{ {} }}",
+                    variable,
+                    iterable,
+                    format_statements(block)
+                )
+            }
+            ExpressionKind::Try(block, catch, finally) => {
+                write!(f, "try {{ {} }}", format_statements(block))?;
+                if let Some((identifier, catch_block)) = catch {
+                    write!(f, " catch ({}) {{ {} }}", identifier, format_statements(catch_block))?;
+                }
+                if let Some(finally_block) = finally {
+                    write!(f, " finally {{ {} }}", format_statements(finally_block))?;
+                }
+                Ok(())
+            }
+            ExpressionKind::Function(params, _block, _name) => {
                 write!(f, "fn({}) {{...}}", params.join(", "),)
             }
-            Expression::FunctionCall(function_expression, args) => {
+            ExpressionKind::FunctionCall(function_expression, args) => {
                 write!(f, "{}({})", function_expression, format_expressions(args))
             }
-            Expression::Assign(left_expression, operator, right_expression) => {
+            ExpressionKind::Assign(left_expression, operator, right_expression) => {
                 write!(f, "{} {} {}", left_expression, operator, right_expression)
             }
-            Expression::Index(left_expression, right_expression) => {
+            ExpressionKind::PrefixUpdate(operator, target) => write!(f, "({}{})", operator, target),
+            ExpressionKind::PostfixUpdate(target, operator) => write!(f, "({}{})", target, operator),
+            ExpressionKind::Index(left_expression, right_expression) => {
                 write!(f, "({}[{}])", left_expression, right_expression)
             }
+            ExpressionKind::Interpolation(parts) => {
+                write!(f, "\"")?;
+                for part in parts {
+                    match &part.kind {
+                        ExpressionKind::Lit(Literal::String(text)) => write!(f, "{}", text.escape_debug())?,
+                        _ => write!(f, "${{{}}}", part)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            ExpressionKind::Slice(left_expression, start, end) => {
+                let start = start.as_ref().map(|start| start.to_string()).unwrap_or_default();
+                let end = end.as_ref().map(|end| end.to_string()).unwrap_or_default();
+
+                write!(f, "({}[{}:{}])", left_expression, start, end)
+            }
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
-    Integer(i32),
+    Integer(i64),
+    BigInt(BigInt),
+    Float(f64),
     Boolean(bool),
     String(String),
     Array(Vec<Expression>),
+    Hash(Vec<(Expression, Expression)>),
 }
 
 impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Literal::Integer(i) => write!(f, "{}", i),
+            Literal::BigInt(i) => write!(f, "{}", i),
+            Literal::Float(float) => write!(f, "{:?}", float),
             Literal::Boolean(b) => write!(f, "{}", b),
-            Literal::String(s) => write!(f, r#""{}""#, s),
+            Literal::String(s) => write!(f, "{:?}", s),
             Literal::Array(arr) => write!(f, "[{}]", format_expressions(arr)),
+            Literal::Hash(pairs) => write!(
+                f,
+                "{{{}}}",
+                pairs
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
         }
     }
 }
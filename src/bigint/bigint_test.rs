@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod bigint_test {
+    use crate::bigint::BigInt;
+
+    fn big(literal: &str) -> BigInt {
+        BigInt::parse(literal).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let tests = [
+            ("0", "0"),
+            ("-0", "0"),
+            ("+42", "42"),
+            ("000123", "123"),
+            ("-9223372036854775808", "-9223372036854775808"),
+            ("123456789012345678901234567890", "123456789012345678901234567890"),
+            ("1000000000000000000", "1000000000000000000"),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(big(input).to_string(), expected);
+        }
+
+        assert_eq!(BigInt::parse(""), None);
+        assert_eq!(BigInt::parse("12a"), None);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let tests = [
+            ("99999999999999999999", "+", "1", "100000000000000000000"),
+            ("-99999999999999999999", "+", "1", "-99999999999999999998"),
+            ("1", "-", "99999999999999999999", "-99999999999999999998"),
+            ("18446744073709551616", "-", "18446744073709551616", "0"),
+            (
+                "-12345678901234567890",
+                "*",
+                "98765432109876543210",
+                "-1219326311370217952237463801111263526900",
+            ),
+            (
+                "1219326311370217952237463801111263526900",
+                "/",
+                "98765432109876543210",
+                "12345678901234567890",
+            ),
+            ("-100000000000000000000", "/", "7", "-14285714285714285714"),
+            ("-100000000000000000000", "%", "7", "-2"),
+            (
+                "100000000000000000000",
+                "%",
+                "-30000000000000000000",
+                "10000000000000000000",
+            ),
+            (
+                "340282366920938463463374607431768211455",
+                "/",
+                "18446744073709551617",
+                "18446744073709551615",
+            ),
+            ("-340282366920938463463374607431768211456", "%", "4294967297", "-1"),
+            (
+                "6277101735386680763835789423207666416102355444464034512895",
+                "/",
+                "340282366920938463463374607431768211456",
+                "18446744073709551615",
+            ),
+            (
+                "6277101735386680763835789423207666416102355444464034512895",
+                "%",
+                "340282366920938463444927863358058659840",
+                "18446744073709551615",
+            ),
+            // The first quotient limb estimated here is one too large.
+            (
+                "170141183420855150474555134919112130560",
+                "/",
+                "39614081257132168796771975169",
+                "4294967294",
+            ),
+            (
+                "170141183420855150474555134919112130560",
+                "%",
+                "39614081257132168796771975169",
+                "39614081257132168792477007874",
+            ),
+        ];
+
+        for (left, operator, right, expected) in tests {
+            let (left, right) = (big(left), big(right));
+            let result = match operator {
+                "+" => &left + &right,
+                "-" => &left - &right,
+                "*" => &left * &right,
+                "/" => left.checked_div(&right).unwrap(),
+                "%" => left.checked_rem(&right).unwrap(),
+                _ => unreachable!(),
+            };
+
+            assert_eq!(result.to_string(), expected, "{} {} {}", left, operator, right);
+        }
+
+        assert_eq!(big("1").checked_div(&big("0")), None);
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(BigInt::from(i64::MAX).to_i64(), Some(i64::MAX));
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(big("-9223372036854775809").to_i64(), None);
+        assert_eq!(big("-18446744073709551616").to_f64(), -18446744073709551616.0);
+        assert_eq!(
+            BigInt::from_f64(-1e20).map(|i| i.to_string()),
+            Some("-100000000000000000000".to_string())
+        );
+        assert_eq!(BigInt::from_f64(2.75).map(|i| i.to_string()), Some("2".to_string()));
+        assert_eq!(BigInt::from_f64(f64::NAN), None);
+        assert_eq!(big("-120").num_digits(), 3);
+        assert_eq!(
+            BigInt::from(-(1i128 << 100)).to_string(),
+            "-1267650600228229401496703205376"
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(big("-100000000000000000000") < big("-1"));
+        assert!(big("100000000000000000000") > big("99999999999999999999"));
+        assert!(big("-1") < big("0"));
+    }
+}
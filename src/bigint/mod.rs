@@ -0,0 +1,452 @@
+use std::{cmp::Ordering, convert::TryFrom, fmt, ops};
+
+mod bigint_test;
+
+const LIMB_BITS: u32 = 32;
+const DECIMAL_BASE: u32 = 1_000_000_000;
+const DECIMAL_DIGITS: usize = 9;
+
+// Sign and magnitude, with the magnitude stored as little-endian base 2^32 limbs.
+// The magnitude never has trailing zero limbs and zero is never negative.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        trim(&mut magnitude);
+
+        BigInt {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    pub fn parse(literal: &str) -> Option<Self> {
+        let (negative, digits) = match literal.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, literal.strip_prefix('+').unwrap_or(literal)),
+        };
+
+        if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut magnitude = vec![];
+        let mut start = 0;
+        let mut end = match digits.len() % DECIMAL_DIGITS {
+            0 => DECIMAL_DIGITS,
+            remainder => remainder,
+        };
+
+        while start < digits.len() {
+            let chunk = digits[start..end].parse::<u32>().ok()?;
+            mul_small_add(&mut magnitude, 10u32.pow((end - start) as u32), chunk);
+            start = end;
+            end += DECIMAL_DIGITS;
+        }
+
+        Some(BigInt::new(negative, magnitude))
+    }
+
+    pub fn from_f64(float: f64) -> Option<Self> {
+        if !float.is_finite() {
+            return None;
+        }
+
+        let bits = float.trunc().to_bits();
+        let negative = bits >> 63 == 1;
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        let mantissa = bits & ((1 << 52) - 1);
+
+        if exponent == 0 {
+            return Some(BigInt::default());
+        }
+
+        let mantissa = mantissa | (1 << 52);
+        let shift = exponent - 1075;
+
+        let magnitude = if shift < 0 {
+            from_u64(mantissa >> (-shift).min(63))
+        } else {
+            shift_left(&from_u64(mantissa), shift as usize)
+        };
+
+        Some(BigInt::new(negative, magnitude))
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+
+        let value = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0u64, |acc, limb| (acc << LIMB_BITS) | *limb as u64);
+
+        if self.negative {
+            match value.cmp(&(1 << 63)) {
+                Ordering::Less => Some(-(value as i64)),
+                Ordering::Equal => Some(i64::MIN),
+                Ordering::Greater => None,
+            }
+        } else {
+            i64::try_from(value).ok()
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let value = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0.0, |acc, limb| acc * 4294967296.0 + *limb as f64);
+
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn num_digits(&self) -> usize {
+        self.to_string().trim_start_matches('-').len()
+    }
+
+    pub fn checked_div(&self, other: &BigInt) -> Option<BigInt> {
+        let (quotient, _) = div_rem(&self.magnitude, &other.magnitude)?;
+        Some(BigInt::new(self.negative != other.negative, quotient))
+    }
+
+    pub fn checked_rem(&self, other: &BigInt) -> Option<BigInt> {
+        let (_, remainder) = div_rem(&self.magnitude, &other.magnitude)?;
+        Some(BigInt::new(self.negative, remainder))
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(integer: i64) -> Self {
+        BigInt::new(integer < 0, from_u64(integer.unsigned_abs()))
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(integer: i128) -> Self {
+        let value = integer.unsigned_abs();
+        let mut magnitude = from_u64(value as u64);
+        magnitude.resize(2, 0);
+        magnitude.extend(from_u64((value >> 64) as u64));
+
+        BigInt::new(integer < 0, magnitude)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        let mut chunks = vec![];
+        let mut magnitude = self.magnitude.clone();
+
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = div_rem_small(&magnitude, DECIMAL_BASE);
+            chunks.push(remainder);
+            magnitude = quotient;
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitudes(&self.magnitude, &other.magnitude),
+            (true, true) => cmp_magnitudes(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl ops::Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl ops::Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitudes(&self.magnitude, &other.magnitude));
+        }
+
+        match cmp_magnitudes(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::new(other.negative, sub_magnitudes(&other.magnitude, &self.magnitude)),
+            _ => BigInt::new(self.negative, sub_magnitudes(&self.magnitude, &other.magnitude)),
+        }
+    }
+}
+
+impl ops::Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl ops::Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != other.negative,
+            mul_magnitudes(&self.magnitude, &other.magnitude),
+        )
+    }
+}
+
+fn trim(magnitude: &mut Vec<u32>) {
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+}
+
+fn from_u64(value: u64) -> Vec<u32> {
+    let mut magnitude = vec![value as u32, (value >> LIMB_BITS) as u32];
+    trim(&mut magnitude);
+    magnitude
+}
+
+fn cmp_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> LIMB_BITS;
+    }
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+
+    result
+}
+
+// Requires `a >= b`.
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+
+    for (i, limb) in a.iter().enumerate() {
+        let mut difference = *limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if difference < 0 {
+            difference += 1 << LIMB_BITS;
+            borrow = 1;
+        }
+        result.push(difference as u32);
+    }
+
+    trim(&mut result);
+    result
+}
+
+fn mul_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+
+    let mut result = vec![0u32; a.len() + b.len()];
+
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, y) in b.iter().enumerate() {
+            let product = *x as u64 * *y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> LIMB_BITS;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+
+    trim(&mut result);
+    result
+}
+
+fn mul_small_add(magnitude: &mut Vec<u32>, multiplier: u32, addend: u32) {
+    let mut carry = addend as u64;
+
+    for limb in magnitude.iter_mut() {
+        let product = *limb as u64 * multiplier as u64 + carry;
+        *limb = product as u32;
+        carry = product >> LIMB_BITS;
+    }
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+fn div_rem_small(magnitude: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; magnitude.len()];
+    let mut remainder = 0u64;
+
+    for (i, limb) in magnitude.iter().enumerate().rev() {
+        let current = (remainder << LIMB_BITS) | *limb as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+
+    trim(&mut quotient);
+    (quotient, remainder as u32)
+}
+
+// Schoolbook long division, one limb of the quotient at a time (Knuth's algorithm D). Both
+// operands are shifted so the divisor's top limb has its high bit set; each quotient limb is then
+// estimated from the top two limbs of the remainder, and is at most one too large after the
+// correction against the divisor's second limb.
+fn div_rem(a: &[u32], b: &[u32]) -> Option<(Vec<u32>, Vec<u32>)> {
+    match b {
+        [] => None,
+        [divisor] => {
+            let (quotient, remainder) = div_rem_small(a, *divisor);
+            Some((quotient, from_u64(remainder as u64)))
+        }
+        _ if cmp_magnitudes(a, b) == Ordering::Less => Some((vec![], a.to_vec())),
+        _ => {
+            let shift = b[b.len() - 1].leading_zeros() as usize;
+            let divisor = shift_left(b, shift);
+            let mut remainder = shift_left(a, shift);
+            remainder.resize(a.len() + 1, 0);
+
+            let n = divisor.len();
+            let (top, second) = (divisor[n - 1] as u64, divisor[n - 2] as u64);
+            let mut quotient = vec![0u32; a.len() - n + 1];
+
+            for j in (0..quotient.len()).rev() {
+                let numerator = (remainder[j + n] as u64) << LIMB_BITS | remainder[j + n - 1] as u64;
+                let mut estimate = numerator / top;
+                let mut rest = numerator % top;
+                while estimate > u32::MAX as u64
+                    || estimate * second > (rest << LIMB_BITS | remainder[j + n - 2] as u64)
+                {
+                    estimate -= 1;
+                    rest += top;
+                    if rest > u32::MAX as u64 {
+                        break;
+                    }
+                }
+
+                let mut carry = 0u64;
+                let mut borrow = 0i64;
+                for (i, limb) in divisor.iter().enumerate() {
+                    let product = estimate * *limb as u64 + carry;
+                    carry = product >> LIMB_BITS;
+                    let difference = remainder[i + j] as i64 - (product as u32) as i64 - borrow;
+                    remainder[i + j] = difference as u32;
+                    borrow = i64::from(difference < 0);
+                }
+                let difference = remainder[j + n] as i64 - carry as i64 - borrow;
+                remainder[j + n] = difference as u32;
+
+                // The estimate was one too large: add the divisor back.
+                if difference < 0 {
+                    estimate -= 1;
+                    let mut carry = 0u64;
+                    for (i, limb) in divisor.iter().enumerate() {
+                        let sum = remainder[i + j] as u64 + *limb as u64 + carry;
+                        remainder[i + j] = sum as u32;
+                        carry = sum >> LIMB_BITS;
+                    }
+                    remainder[j + n] = remainder[j + n].wrapping_add(carry as u32);
+                }
+
+                quotient[j] = estimate as u32;
+            }
+
+            trim(&mut quotient);
+            remainder.truncate(n);
+            let mut remainder = shift_right(&remainder, shift);
+            trim(&mut remainder);
+            Some((quotient, remainder))
+        }
+    }
+}
+
+fn shift_left(magnitude: &[u32], bits: usize) -> Vec<u32> {
+    if magnitude.is_empty() {
+        return vec![];
+    }
+
+    let limbs = bits / LIMB_BITS as usize;
+    let bits = (bits % LIMB_BITS as usize) as u32;
+
+    let mut result = vec![0u32; limbs];
+    let mut carry = 0u32;
+
+    for limb in magnitude {
+        if bits == 0 {
+            result.push(*limb);
+        } else {
+            result.push((limb << bits) | carry);
+            carry = limb >> (LIMB_BITS - bits);
+        }
+    }
+    if carry > 0 {
+        result.push(carry);
+    }
+
+    result
+}
+
+// Requires `bits < LIMB_BITS`.
+fn shift_right(magnitude: &[u32], bits: usize) -> Vec<u32> {
+    if bits == 0 {
+        return magnitude.to_vec();
+    }
+
+    let bits = bits as u32;
+    (0..magnitude.len())
+        .map(|i| {
+            let high = magnitude.get(i + 1).map_or(0, |limb| limb << (LIMB_BITS - bits));
+            (magnitude[i] >> bits) | high
+        })
+        .collect()
+}
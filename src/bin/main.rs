@@ -1,5 +1,12 @@
-use limoo::repl;
+use std::env;
+
+use limoo::repl::{self, Backend};
 
 fn main() {
-    repl::start();
+    let backend = match env::args().nth(1).as_deref() {
+        Some("--vm") => Backend::Vm,
+        _ => Backend::Evaluator,
+    };
+
+    repl::start(backend);
 }
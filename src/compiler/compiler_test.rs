@@ -0,0 +1,436 @@
+#[cfg(test)]
+mod compiler_test {
+    use crate::{
+        compiler::{instruction::Instruction, Compiler},
+        evaluator::builtins::Builtin,
+        object::Object,
+        parser::parser_test::parse,
+    };
+
+    use Instruction::*;
+
+    fn test_runner(test_case: &[(&str, &[Object], &[Instruction])]) {
+        for (input, expected_constants, expected_instructions) in test_case {
+            let node = parse(input).expect("Parsing Error");
+            let bytecode = Compiler::default().compile(node).expect("Compiler Error");
+
+            let constants = bytecode
+                .constants
+                .iter()
+                .map(|c| (**c).clone())
+                .collect::<Vec<Object>>();
+
+            assert_eq!(expected_constants, &constants.as_slice(), "constants of: {}", input);
+            assert_eq!(
+                expected_instructions,
+                &bytecode.instructions.as_slice(),
+                "instructions of: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_integer_arithmetic() {
+        let tests: [(&str, &[Object], &[Instruction]); 5] = [
+            (
+                "1 + 2",
+                &[Object::Integer(1), Object::Integer(2)],
+                &[Constant(0), Constant(1), Add, ReturnValue],
+            ),
+            (
+                "1; 2",
+                &[Object::Integer(1), Object::Integer(2)],
+                &[Constant(0), Pop, Constant(1), ReturnValue],
+            ),
+            (
+                "1 < 2",
+                &[Object::Integer(1), Object::Integer(2)],
+                &[Constant(0), Constant(1), LessThan, ReturnValue],
+            ),
+            ("-1", &[Object::Integer(1)], &[Constant(0), Minus, ReturnValue]),
+            (
+                "7 % 2",
+                &[Object::Integer(7), Object::Integer(2)],
+                &[Constant(0), Constant(1), Mod, ReturnValue],
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let tests: [(&str, &[Object], &[Instruction]); 2] = [
+            (
+                "if (true) { 10 }; 3333;",
+                &[Object::Integer(10), Object::Integer(3333)],
+                &[
+                    True,
+                    JumpNotTruthy(4),
+                    Constant(0),
+                    Jump(5),
+                    Null,
+                    Pop,
+                    Constant(1),
+                    ReturnValue,
+                ],
+            ),
+            (
+                "if (true) { 10 } else { 20 }",
+                &[Object::Integer(10), Object::Integer(20)],
+                &[True, JumpNotTruthy(4), Constant(0), Jump(5), Constant(1), ReturnValue],
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_logical_operators() {
+        let tests: [(&str, &[Object], &[Instruction]); 2] = [
+            ("true && false", &[], &[True, JumpNotTruthyOrPop(3), False, ReturnValue]),
+            (
+                "1 || 2; 3",
+                &[Object::Integer(1), Object::Integer(2), Object::Integer(3)],
+                &[
+                    Constant(0),
+                    JumpTruthyOrPop(3),
+                    Constant(1),
+                    Pop,
+                    Constant(2),
+                    ReturnValue,
+                ],
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_while_loop() {
+        let tests: [(&str, &[Object], &[Instruction]); 1] = [(
+            "while (true) { 1 }",
+            &[Object::Integer(1)],
+            &[
+                EnterLoop,
+                True,
+                JumpNotTruthy(6),
+                Constant(0),
+                Pop,
+                Jump(1),
+                ExitLoop,
+                Null,
+                ReturnValue,
+            ],
+        )];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_try_catch_finally() {
+        let tests: [(&str, &[Object], &[Instruction]); 2] = [
+            (
+                "try { throw 1; } catch (e) { e }",
+                &[Object::Integer(1)],
+                &[
+                    PushHandler(5),
+                    Constant(0),
+                    Throw,
+                    PopHandler,
+                    Jump(9),
+                    NewLocal(0),
+                    SetLocal(0),
+                    Pop,
+                    GetLocal(0),
+                    ReturnValue,
+                ],
+            ),
+            (
+                "try { 1 } finally { 2 }",
+                &[Object::Integer(1), Object::Integer(2), Object::Integer(2)],
+                &[
+                    PushHandler(6),
+                    Constant(0),
+                    PopHandler,
+                    Constant(1),
+                    Pop,
+                    Jump(9),
+                    Constant(2),
+                    Pop,
+                    Throw,
+                    ReturnValue,
+                ],
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_for_in_loop() {
+        let tests: [(&str, &[Object], &[Instruction]); 1] = [(
+            "for (x in [1]) { x }",
+            &[Object::Integer(1)],
+            &[
+                Constant(0),
+                Array(1),
+                Iterate,
+                EnterLoop,
+                IterateNext(11),
+                NewLocal(0),
+                SetLocal(0),
+                Pop,
+                GetLocal(0),
+                Pop,
+                Jump(4),
+                ExitLoop,
+                Pop,
+                Pop,
+                Null,
+                ReturnValue,
+            ],
+        )];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_slice_expression() {
+        let tests: [(&str, &[Object], &[Instruction]); 2] = [
+            (
+                "[1, 2][0:1]",
+                &[
+                    Object::Integer(1),
+                    Object::Integer(2),
+                    Object::Integer(0),
+                    Object::Integer(1),
+                ],
+                &[
+                    Constant(0),
+                    Constant(1),
+                    Array(2),
+                    Constant(2),
+                    Constant(3),
+                    Slice,
+                    ReturnValue,
+                ],
+            ),
+            (
+                "\"abc\"[1:]",
+                &[Object::String("abc".to_string()), Object::Integer(1)],
+                &[Constant(0), Constant(1), Null, Slice, ReturnValue],
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_global_let_statements() {
+        let tests: [(&str, &[Object], &[Instruction]); 3] = [
+            (
+                "let one = 1; let two = one;",
+                &[Object::Integer(1)],
+                &[Constant(0), SetGlobal(0), Pop, GetGlobal(0), SetGlobal(1), ReturnValue],
+            ),
+            (
+                "let one = 1; one = 2;",
+                &[Object::Integer(1), Object::Integer(2)],
+                &[
+                    Constant(0),
+                    SetGlobal(0),
+                    Pop,
+                    Constant(1),
+                    AssignGlobal(0),
+                    ReturnValue,
+                ],
+            ),
+            (
+                "let xs = [1]; xs[0] = 2;",
+                &[Object::Integer(1), Object::Integer(0), Object::Integer(2)],
+                &[
+                    Constant(0),
+                    Array(1),
+                    SetGlobal(0),
+                    Pop,
+                    GetGlobal(0),
+                    Constant(1),
+                    Constant(2),
+                    Dup,
+                    SetIndexGlobal(0, 1),
+                    ReturnValue,
+                ],
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        let tests: [(&str, &[Object], &[Instruction]); 3] = [
+            (
+                "let n = 1; n += 2;",
+                &[Object::Integer(1), Object::Integer(2)],
+                &[
+                    Constant(0),
+                    SetGlobal(0),
+                    Pop,
+                    GetGlobal(0),
+                    Constant(1),
+                    Add,
+                    AssignGlobal(0),
+                    ReturnValue,
+                ],
+            ),
+            (
+                "let n = 1; n--;",
+                &[Object::Integer(1), Object::Integer(1)],
+                &[
+                    Constant(0),
+                    SetGlobal(0),
+                    Pop,
+                    GetGlobal(0),
+                    Dup,
+                    Constant(1),
+                    Sub,
+                    AssignGlobal(0),
+                    Pop,
+                    ReturnValue,
+                ],
+            ),
+            (
+                "let m = [[1]]; m[0][0] *= 3;",
+                &[
+                    Object::Integer(1),
+                    Object::Integer(0),
+                    Object::Integer(0),
+                    Object::Integer(3),
+                ],
+                &[
+                    Constant(0),
+                    Array(1),
+                    Array(1),
+                    SetGlobal(0),
+                    Pop,
+                    GetGlobal(0),
+                    Constant(1),
+                    IndexKeep,
+                    Constant(2),
+                    IndexKeep,
+                    Constant(3),
+                    Mul,
+                    Dup,
+                    SetIndexGlobal(0, 2),
+                    ReturnValue,
+                ],
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_builtins() {
+        let tests: [(&str, &[Object], &[Instruction]); 1] = [(
+            "len([])",
+            &[],
+            &[GetBuiltin(Builtin::Len), Array(0), Call(1), ReturnValue],
+        )];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_block_scoped_let() {
+        let tests: [(&str, &[Object], &[Instruction]); 2] = [
+            (
+                "if (true) { let a = 1; a }",
+                &[Object::Integer(1)],
+                &[
+                    True,
+                    JumpNotTruthy(8),
+                    Constant(0),
+                    NewLocal(0),
+                    SetLocal(0),
+                    Pop,
+                    GetLocal(0),
+                    Jump(9),
+                    Null,
+                    ReturnValue,
+                ],
+            ),
+            (
+                "let b = 1; if (true) { b = 2 }",
+                &[Object::Integer(1), Object::Integer(2)],
+                &[
+                    Constant(0),
+                    SetGlobal(0),
+                    Pop,
+                    True,
+                    JumpNotTruthy(8),
+                    Constant(1),
+                    AssignGlobal(0),
+                    Jump(9),
+                    Null,
+                    ReturnValue,
+                ],
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_closures() {
+        let node = parse("fn(a) { fn(b) { a + b } }").expect("Parsing Error");
+        let bytecode = Compiler::default().compile(node).expect("Compiler Error");
+
+        match &*bytecode.constants[0] {
+            Object::CompiledFunction(inner) => {
+                assert_eq!(inner.instructions, vec![GetFree(0), GetLocal(0), Add, ReturnValue]);
+                assert_eq!(inner.num_locals, 1);
+                assert_eq!(inner.captures.len(), 1);
+            }
+            object => panic!("Expected compiled function, got {}", object),
+        }
+
+        match &*bytecode.constants[1] {
+            Object::CompiledFunction(outer) => {
+                assert_eq!(outer.instructions, vec![Closure(0), ReturnValue]);
+                assert!(outer.captures.is_empty());
+            }
+            object => panic!("Expected compiled function, got {}", object),
+        }
+
+        assert_eq!(bytecode.instructions, vec![Closure(1), ReturnValue]);
+    }
+
+    #[test]
+    fn test_tail_calls() {
+        let node = parse("fn(f, n) { if (n) { f(n) } else { f(1) + 1 } }").expect("Parsing Error");
+        let bytecode = Compiler::default().compile(node).expect("Compiler Error");
+
+        match &*bytecode.constants[2] {
+            Object::CompiledFunction(function) => assert_eq!(
+                function.instructions,
+                vec![
+                    GetLocal(1),
+                    JumpNotTruthy(6),
+                    GetLocal(0),
+                    GetLocal(1),
+                    TailCall(1),
+                    Jump(11),
+                    GetLocal(0),
+                    Constant(0),
+                    Call(1),
+                    Constant(1),
+                    Add,
+                    ReturnValue,
+                ]
+            ),
+            object => panic!("Expected compiled function, got {}", object),
+        }
+
+        let node = parse("fn(f) { try { return f(); } catch (e) { f() } }").expect("Parsing Error");
+        let bytecode = Compiler::default().compile(node).expect("Compiler Error");
+
+        match &*bytecode.constants[0] {
+            Object::CompiledFunction(function) => {
+                assert_eq!(function.instructions[1..4], [GetLocal(0), Call(0), PopHandler]);
+            }
+            object => panic!("Expected compiled function, got {}", object),
+        }
+    }
+}
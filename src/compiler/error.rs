@@ -0,0 +1,25 @@
+use std::fmt;
+
+use crate::token::Span;
+
+#[derive(Debug, Clone)]
+pub struct CompilerError {
+    message: String,
+    span: Span,
+}
+
+impl fmt::Display for CompilerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl CompilerError {
+    pub fn new(message: String, span: Span) -> Self {
+        CompilerError { message, span }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
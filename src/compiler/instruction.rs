@@ -0,0 +1,117 @@
+use crate::{evaluator::builtins::Builtin, token::Token};
+
+pub type Instructions = Vec<Instruction>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Constant(usize),
+    Null,
+    True,
+    False,
+    Pop,
+    Dup,
+
+    // Operators
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Equal,
+    NotEqual,
+    LessThan,
+    GreaterThan,
+    Range,
+    RangeInclusive,
+    Minus,
+    Bang,
+
+    // Control flow
+    Jump(usize),
+    JumpNotTruthy(usize),
+    JumpNotTruthyOrPop(usize),
+    JumpTruthyOrPop(usize),
+    EnterLoop,
+    ExitLoop,
+    Break(usize),
+    Continue(usize),
+    Iterate,
+    IterateNext(usize),
+
+    // Bindings
+    GetGlobal(usize),
+    SetGlobal(usize),
+    AssignGlobal(usize),
+    GetLocal(usize),
+    SetLocal(usize),
+    NewLocal(usize),
+    GetFree(usize),
+    SetFree(usize),
+    GetBuiltin(Builtin),
+
+    Array(usize),
+    Hash(usize),
+    Interpolate(usize),
+    Index,
+    IndexKeep,
+    SetIndexGlobal(usize, usize),
+    SetIndexLocal(usize, usize),
+    SetIndexFree(usize, usize),
+    Slice,
+
+    // Functions
+    Closure(usize),
+    Call(usize),
+    TailCall(usize),
+    ReturnValue,
+
+    // Exceptions
+    PushHandler(usize),
+    PopHandler,
+    Throw,
+}
+
+impl Instruction {
+    pub fn from_infix_operator(operator: &Token) -> Option<Self> {
+        match operator {
+            Token::PLUS => Some(Instruction::Add),
+            Token::MINUS => Some(Instruction::Sub),
+            Token::ASTERISK => Some(Instruction::Mul),
+            Token::SLASH => Some(Instruction::Div),
+            Token::PERCENT => Some(Instruction::Mod),
+            Token::EQ => Some(Instruction::Equal),
+            Token::NOT_EQ => Some(Instruction::NotEqual),
+            Token::LT => Some(Instruction::LessThan),
+            Token::GT => Some(Instruction::GreaterThan),
+            Token::DOTDOT => Some(Instruction::Range),
+            Token::DOTDOT_EQ => Some(Instruction::RangeInclusive),
+            _ => None,
+        }
+    }
+
+    pub fn from_prefix_operator(operator: &Token) -> Option<Self> {
+        match operator {
+            Token::MINUS => Some(Instruction::Minus),
+            Token::BANG => Some(Instruction::Bang),
+            _ => None,
+        }
+    }
+
+    pub fn operator(&self) -> Option<Token> {
+        match self {
+            Instruction::Add => Some(Token::PLUS),
+            Instruction::Sub | Instruction::Minus => Some(Token::MINUS),
+            Instruction::Mul => Some(Token::ASTERISK),
+            Instruction::Div => Some(Token::SLASH),
+            Instruction::Mod => Some(Token::PERCENT),
+            Instruction::Equal => Some(Token::EQ),
+            Instruction::NotEqual => Some(Token::NOT_EQ),
+            Instruction::LessThan => Some(Token::LT),
+            Instruction::GreaterThan => Some(Token::GT),
+            Instruction::Range => Some(Token::DOTDOT),
+            Instruction::RangeInclusive => Some(Token::DOTDOT_EQ),
+            Instruction::Bang => Some(Token::BANG),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,817 @@
+use std::{fmt, mem, rc::Rc};
+
+use crate::{
+    ast::{BlockStatement, Expression, ExpressionKind, Literal, Node, Statement, StatementKind},
+    evaluator::builtins::Builtin,
+    object::Object,
+    token::{Span, Token},
+};
+
+use self::{
+    error::CompilerError,
+    instruction::{Instruction, Instructions},
+    symbol_table::{Symbol, SymbolScope, SymbolTable},
+};
+
+mod compiler_test;
+pub mod error;
+pub mod instruction;
+pub mod symbol_table;
+
+type CompilerResult = Result<(), CompilerError>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledFunction {
+    pub instructions: Instructions,
+    pub spans: Vec<Span>,
+    pub num_locals: usize,
+    pub parameters: Vec<String>,
+    pub captures: Vec<Symbol>,
+    pub name: Option<String>,
+}
+
+impl fmt::Display for CompiledFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "fn({}) {{...}}", self.parameters.join(","))
+    }
+}
+
+#[derive(Debug)]
+pub struct Bytecode {
+    pub instructions: Instructions,
+    pub spans: Vec<Span>,
+    pub constants: Vec<Rc<Object>>,
+    pub global_names: Vec<String>,
+    pub num_locals: usize,
+}
+
+#[derive(Debug, Default)]
+struct CompilationScope {
+    instructions: Instructions,
+    spans: Vec<Span>,
+    loops: Vec<LoopContext>,
+    tries: Vec<TryContext>,
+}
+
+#[derive(Debug, Default)]
+struct LoopContext {
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+// A `try` being compiled: how many handlers it currently has pushed, and the `finally` block
+// that a `break`, `continue` or `return` leaving it has to run first.
+#[derive(Debug)]
+struct TryContext {
+    handlers: usize,
+    finally: Option<BlockStatement>,
+    loop_depth: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Compiler {
+    constants: Vec<Rc<Object>>,
+    symbol_table: SymbolTable,
+    scopes: Vec<CompilationScope>,
+    span: Span,
+}
+
+impl Compiler {
+    pub fn compile(&mut self, node: Node) -> Result<Bytecode, CompilerError> {
+        self.scopes = vec![CompilationScope::default()];
+
+        let result = match node {
+            Node::Program(program) => self.compile_block(&program),
+            Node::Stmt(statement) => self.compile_statement(&statement),
+            Node::Expr(expression) => self.compile_expression(&expression),
+        };
+
+        if let Err(err) = result {
+            while self.scopes.len() > 1 {
+                self.leave_scope();
+            }
+            self.symbol_table.leave_all_blocks();
+            return Err(err);
+        }
+
+        self.emit(Instruction::ReturnValue);
+
+        let main_scope = self.scopes.pop().unwrap_or_default();
+
+        Ok(Bytecode {
+            instructions: main_scope.instructions,
+            spans: main_scope.spans,
+            constants: self.constants.clone(),
+            global_names: self.symbol_table.global_names(),
+            num_locals: self.symbol_table.num_definitions(),
+        })
+    }
+
+    fn compile_block(&mut self, statements: &[Statement]) -> CompilerResult {
+        if statements.is_empty() {
+            self.emit(Instruction::Null);
+            return Ok(());
+        }
+
+        for (i, statement) in statements.iter().enumerate() {
+            self.compile_statement(statement)?;
+
+            if i != statements.len() - 1 {
+                self.emit(Instruction::Pop);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_scoped_block(&mut self, statements: &[Statement]) -> CompilerResult {
+        self.symbol_table.enter_block();
+        let result = self.compile_block(statements);
+        self.symbol_table.leave_block();
+        result
+    }
+
+    fn compile_loop_body(&mut self, body: &[Statement]) -> CompilerResult {
+        for statement in body {
+            self.compile_statement(statement)?;
+            self.emit(Instruction::Pop);
+        }
+
+        Ok(())
+    }
+
+    fn leave_loop(&mut self, continue_target: usize, loop_end: usize) {
+        if let Some(context) = self.current_scope().loops.pop() {
+            for position in context.breaks {
+                self.replace_instruction(position, Instruction::Break(loop_end));
+            }
+            for position in context.continues {
+                self.replace_instruction(position, Instruction::Continue(continue_target));
+            }
+        }
+    }
+
+    // With a `finally` block, an outer handler runs it and rethrows when the `try` or `catch`
+    // block fails; the normal path runs it inline after the outer handler is popped.
+    fn compile_try(
+        &mut self,
+        body: &[Statement],
+        catch: Option<&(String, BlockStatement)>,
+        finally: Option<&BlockStatement>,
+    ) -> CompilerResult {
+        let loop_depth = self.current_scope().loops.len();
+        let rethrow = finally.map(|_| self.emit(Instruction::PushHandler(0)));
+        let handler = catch.map(|_| self.emit(Instruction::PushHandler(0)));
+
+        self.current_scope().tries.push(TryContext {
+            handlers: usize::from(rethrow.is_some()) + usize::from(handler.is_some()),
+            finally: finally.cloned(),
+            loop_depth,
+        });
+        let result = self.compile_try_blocks(body, catch, handler);
+        let context = self.current_scope().tries.pop();
+        result?;
+
+        if let (Some(rethrow), Some(finally)) = (rethrow, context.and_then(|context| context.finally)) {
+            self.emit(Instruction::PopHandler);
+            self.compile_scoped_block(&finally)?;
+            self.emit(Instruction::Pop);
+            let jump = self.emit(Instruction::Jump(0));
+
+            self.replace_instruction(rethrow, Instruction::PushHandler(self.current_position()));
+            self.compile_scoped_block(&finally)?;
+            self.emit(Instruction::Pop);
+            self.emit(Instruction::Throw);
+
+            self.replace_instruction(jump, Instruction::Jump(self.current_position()));
+        }
+
+        Ok(())
+    }
+
+    fn compile_try_blocks(
+        &mut self,
+        body: &[Statement],
+        catch: Option<&(String, BlockStatement)>,
+        handler: Option<usize>,
+    ) -> CompilerResult {
+        self.compile_scoped_block(body)?;
+
+        if let (Some((identifier, catch_body)), Some(handler)) = (catch, handler) {
+            self.emit(Instruction::PopHandler);
+            let jump = self.emit(Instruction::Jump(0));
+
+            self.replace_instruction(handler, Instruction::PushHandler(self.current_position()));
+            if let Some(context) = self.current_scope().tries.last_mut() {
+                context.handlers -= 1;
+            }
+
+            self.symbol_table.enter_block();
+            let symbol = self.define_symbol(identifier);
+            self.emit_set(symbol);
+            self.emit(Instruction::Pop);
+            let result = self.compile_block(catch_body);
+            self.symbol_table.leave_block();
+            result?;
+
+            self.replace_instruction(jump, Instruction::Jump(self.current_position()));
+        }
+
+        Ok(())
+    }
+
+    fn leave_loop_tries(&mut self) -> CompilerResult {
+        let loop_depth = self.current_scope().loops.len();
+        let tries = &self.current_scope().tries;
+        let depth = tries
+            .iter()
+            .position(|context| context.loop_depth >= loop_depth)
+            .unwrap_or(tries.len());
+        self.leave_tries(depth)
+    }
+
+    // Pops the handlers of the tries above `depth` and runs their `finally` blocks, innermost
+    // first, before a jump out of them. Each `finally` is compiled as if it sat outside its `try`.
+    fn leave_tries(&mut self, depth: usize) -> CompilerResult {
+        let mut left = vec![];
+        let mut result = Ok(());
+
+        while self.current_scope().tries.len() > depth {
+            let context = self.current_scope().tries.pop().unwrap();
+
+            for _ in 0..context.handlers {
+                self.emit(Instruction::PopHandler);
+            }
+            if let Some(finally) = &context.finally {
+                result = self.compile_scoped_block(finally);
+                self.emit(Instruction::Pop);
+            }
+
+            left.push(context);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        while let Some(context) = left.pop() {
+            self.current_scope().tries.push(context);
+        }
+        result
+    }
+
+    fn compile_statement(&mut self, statement: &Statement) -> CompilerResult {
+        let outer_span = mem::replace(&mut self.span, statement.span);
+        let result = self.compile_statement_kind(&statement.kind);
+        self.span = outer_span;
+        result
+    }
+
+    fn compile_statement_kind(&mut self, statement: &StatementKind) -> CompilerResult {
+        match statement {
+            StatementKind::Let(identifier, expression) => {
+                let symbol = match expression.kind {
+                    ExpressionKind::Function(..) => {
+                        let symbol = self.define_symbol(identifier);
+                        self.compile_expression(expression)?;
+                        symbol
+                    }
+                    _ => {
+                        self.compile_expression(expression)?;
+                        self.define_symbol(identifier)
+                    }
+                };
+                self.emit_set(symbol);
+            }
+            StatementKind::Return(expression) => {
+                self.compile_expression(expression)?;
+                self.leave_tries(0)?;
+                self.emit(Instruction::ReturnValue);
+            }
+            StatementKind::Break => {
+                if self.current_scope().loops.is_empty() {
+                    return Err(CompilerError::new("`break` outside of a loop".to_string(), self.span));
+                }
+
+                self.leave_loop_tries()?;
+                let position = self.emit(Instruction::Break(0));
+                if let Some(context) = self.current_scope().loops.last_mut() {
+                    context.breaks.push(position);
+                }
+            }
+            StatementKind::Continue => {
+                if self.current_scope().loops.is_empty() {
+                    return Err(CompilerError::new(
+                        "`continue` outside of a loop".to_string(),
+                        self.span,
+                    ));
+                }
+
+                self.leave_loop_tries()?;
+                let position = self.emit(Instruction::Continue(0));
+                if let Some(context) = self.current_scope().loops.last_mut() {
+                    context.continues.push(position);
+                }
+            }
+            StatementKind::Throw(expression) => {
+                self.compile_expression(expression)?;
+                self.emit(Instruction::Throw);
+            }
+            StatementKind::Expr(expression) => self.compile_expression(expression)?,
+        }
+
+        Ok(())
+    }
+
+    fn compile_expression(&mut self, expression: &Expression) -> CompilerResult {
+        let outer_span = mem::replace(&mut self.span, expression.span);
+        let result = self.compile_expression_kind(&expression.kind);
+        self.span = outer_span;
+        result
+    }
+
+    fn compile_expression_kind(&mut self, expression: &ExpressionKind) -> CompilerResult {
+        match expression {
+            ExpressionKind::Lit(literal) => self.compile_literal(literal)?,
+            ExpressionKind::Ident(identifier) => self.compile_identifier(identifier),
+            ExpressionKind::Prefix(operator, right) => {
+                self.compile_expression(right)?;
+
+                match Instruction::from_prefix_operator(operator) {
+                    Some(instruction) => self.emit(instruction),
+                    None => return Err(CompilerError::new(format!("Unknown operator: {}", operator), self.span)),
+                };
+            }
+            ExpressionKind::Infix(left, Token::AND, right) => {
+                self.compile_expression(left)?;
+                let jump = self.emit(Instruction::JumpNotTruthyOrPop(0));
+
+                self.compile_expression(right)?;
+                self.replace_instruction(jump, Instruction::JumpNotTruthyOrPop(self.current_position()));
+            }
+            ExpressionKind::Infix(left, Token::OR, right) => {
+                self.compile_expression(left)?;
+                let jump = self.emit(Instruction::JumpTruthyOrPop(0));
+
+                self.compile_expression(right)?;
+                self.replace_instruction(jump, Instruction::JumpTruthyOrPop(self.current_position()));
+            }
+            ExpressionKind::Infix(left, operator, right) => {
+                self.compile_expression(left)?;
+                self.compile_expression(right)?;
+
+                match Instruction::from_infix_operator(operator) {
+                    Some(instruction) => self.emit(instruction),
+                    None => return Err(CompilerError::new(format!("Unknown operator: {}", operator), self.span)),
+                };
+            }
+            ExpressionKind::If(condition, consequence, alternative) => {
+                self.compile_expression(condition)?;
+                let jump_not_truthy = self.emit(Instruction::JumpNotTruthy(0));
+
+                self.compile_scoped_block(consequence)?;
+                let jump = self.emit(Instruction::Jump(0));
+
+                self.replace_instruction(jump_not_truthy, Instruction::JumpNotTruthy(self.current_position()));
+
+                match alternative {
+                    Some(alternative) => self.compile_scoped_block(alternative)?,
+                    None => {
+                        self.emit(Instruction::Null);
+                    }
+                }
+
+                self.replace_instruction(jump, Instruction::Jump(self.current_position()));
+            }
+            ExpressionKind::While(condition, body) => {
+                self.emit(Instruction::EnterLoop);
+                let loop_start = self.current_position();
+
+                self.compile_expression(condition)?;
+                let jump_not_truthy = self.emit(Instruction::JumpNotTruthy(0));
+
+                self.current_scope().loops.push(LoopContext::default());
+                self.symbol_table.enter_block();
+                self.compile_loop_body(body)?;
+                self.symbol_table.leave_block();
+                self.emit(Instruction::Jump(loop_start));
+
+                let loop_end = self.current_position();
+                self.replace_instruction(jump_not_truthy, Instruction::JumpNotTruthy(loop_end));
+                self.leave_loop(loop_start, loop_end);
+
+                self.emit(Instruction::ExitLoop);
+                self.emit(Instruction::Null);
+            }
+            ExpressionKind::For(init, condition, update, body) => {
+                self.symbol_table.enter_block();
+
+                if let Some(init) = init {
+                    self.compile_statement(init)?;
+                    self.emit(Instruction::Pop);
+                }
+                let loop_variables = self.symbol_table.current_block_locals();
+
+                self.emit(Instruction::EnterLoop);
+                let loop_start = self.current_position();
+
+                let jump_not_truthy = match condition {
+                    Some(condition) => {
+                        self.compile_expression(condition)?;
+                        Some(self.emit(Instruction::JumpNotTruthy(0)))
+                    }
+                    None => None,
+                };
+
+                self.current_scope().loops.push(LoopContext::default());
+                self.symbol_table.enter_block();
+                self.compile_loop_body(body)?;
+                self.symbol_table.leave_block();
+
+                // Like `for-in`, every iteration gets its own bindings: they are copied into fresh
+                // cells before the update, so closures from the body keep the values they saw.
+                let continue_target = self.current_position();
+                for symbol in loop_variables {
+                    self.emit(Instruction::GetLocal(symbol.index));
+                    self.emit(Instruction::NewLocal(symbol.index));
+                    self.emit(Instruction::SetLocal(symbol.index));
+                    self.emit(Instruction::Pop);
+                }
+                if let Some(update) = update {
+                    self.compile_expression(update)?;
+                    self.emit(Instruction::Pop);
+                }
+                self.emit(Instruction::Jump(loop_start));
+
+                let loop_end = self.current_position();
+                if let Some(jump_not_truthy) = jump_not_truthy {
+                    self.replace_instruction(jump_not_truthy, Instruction::JumpNotTruthy(loop_end));
+                }
+                self.leave_loop(continue_target, loop_end);
+
+                self.emit(Instruction::ExitLoop);
+                self.symbol_table.leave_block();
+                self.emit(Instruction::Null);
+            }
+            ExpressionKind::ForIn(variable, iterable, body) => {
+                self.compile_expression(iterable)?;
+                self.emit(Instruction::Iterate);
+
+                self.emit(Instruction::EnterLoop);
+                let loop_start = self.current_position();
+                let iterate_next = self.emit(Instruction::IterateNext(0));
+
+                self.current_scope().loops.push(LoopContext::default());
+                self.symbol_table.enter_block();
+                let symbol = self.define_symbol(variable);
+                self.emit_set(symbol);
+                self.emit(Instruction::Pop);
+                self.compile_loop_body(body)?;
+                self.symbol_table.leave_block();
+                self.emit(Instruction::Jump(loop_start));
+
+                let loop_end = self.current_position();
+                self.replace_instruction(iterate_next, Instruction::IterateNext(loop_end));
+                self.leave_loop(loop_start, loop_end);
+
+                self.emit(Instruction::ExitLoop);
+                self.emit(Instruction::Pop);
+                self.emit(Instruction::Pop);
+                self.emit(Instruction::Null);
+            }
+            ExpressionKind::Try(body, catch, finally) => self.compile_try(body, catch.as_ref(), finally.as_ref())?,
+            ExpressionKind::Function(params, body, name) => {
+                self.enter_scope();
+
+                for param in params {
+                    self.symbol_table.define(param);
+                }
+
+                self.compile_block(body)?;
+                self.emit(Instruction::ReturnValue);
+
+                let (mut scope, num_locals, captures) = self.leave_scope();
+                mark_tail_calls(&mut scope.instructions);
+
+                let function = CompiledFunction {
+                    instructions: scope.instructions,
+                    spans: scope.spans,
+                    num_locals,
+                    parameters: params.clone(),
+                    captures,
+                    name: name.clone(),
+                };
+                let index = self.add_constant(Object::CompiledFunction(Rc::new(function)));
+                self.emit(Instruction::Closure(index));
+            }
+            ExpressionKind::FunctionCall(function, args) => {
+                self.compile_expression(function)?;
+
+                for arg in args {
+                    self.compile_expression(arg)?;
+                }
+
+                self.emit(Instruction::Call(args.len()));
+            }
+            ExpressionKind::Assign(target, operator, expression) => {
+                let compile_value = |compiler: &mut Self| compiler.compile_expression(expression);
+                self.compile_assignment(target, operator.binary_operator(), &compile_value, false)?;
+            }
+            ExpressionKind::PrefixUpdate(operator, target) => {
+                self.compile_assignment(target, operator.binary_operator(), &Self::compile_one, false)?;
+            }
+            ExpressionKind::PostfixUpdate(target, operator) => {
+                self.compile_assignment(target, operator.binary_operator(), &Self::compile_one, true)?;
+            }
+            ExpressionKind::Index(left, index) => {
+                self.compile_expression(left)?;
+                self.compile_expression(index)?;
+                self.emit(Instruction::Index);
+            }
+            ExpressionKind::Interpolation(parts) => {
+                for part in parts {
+                    self.compile_expression(part)?;
+                }
+                self.emit(Instruction::Interpolate(parts.len()));
+            }
+            ExpressionKind::Slice(left, start, end) => {
+                self.compile_expression(left)?;
+                for bound in [start, end] {
+                    match bound {
+                        Some(bound) => self.compile_expression(bound)?,
+                        None => {
+                            self.emit(Instruction::Null);
+                        }
+                    }
+                }
+                self.emit(Instruction::Slice);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_literal(&mut self, literal: &Literal) -> CompilerResult {
+        match literal {
+            Literal::Integer(i) => {
+                let index = self.add_constant(Object::Integer(*i));
+                self.emit(Instruction::Constant(index));
+            }
+            Literal::BigInt(i) => {
+                let index = self.add_constant(Object::BigInt(i.clone()));
+                self.emit(Instruction::Constant(index));
+            }
+            Literal::Float(float) => {
+                let index = self.add_constant(Object::Float(*float));
+                self.emit(Instruction::Constant(index));
+            }
+            Literal::Boolean(true) => {
+                self.emit(Instruction::True);
+            }
+            Literal::Boolean(false) => {
+                self.emit(Instruction::False);
+            }
+            Literal::String(s) => {
+                let index = self.add_constant(Object::String(s.clone()));
+                self.emit(Instruction::Constant(index));
+            }
+            Literal::Array(arr) => {
+                for element in arr {
+                    self.compile_expression(element)?;
+                }
+                self.emit(Instruction::Array(arr.len()));
+            }
+            Literal::Hash(pairs) => {
+                for (key, value) in pairs {
+                    self.compile_expression(key)?;
+                    self.compile_expression(value)?;
+                }
+                self.emit(Instruction::Hash(pairs.len()));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_identifier(&mut self, identifier: &str) {
+        if let Some(symbol) = self.symbol_table.resolve(identifier) {
+            self.emit_get(symbol);
+            return;
+        }
+
+        match Builtin::lookup(identifier) {
+            Some(Object::Builtin(builtin)) => {
+                self.emit(Instruction::GetBuiltin(builtin));
+            }
+            _ => {
+                let symbol = self.symbol_table.define_global(identifier);
+                self.emit_get(symbol);
+            }
+        }
+    }
+
+    fn emit_get(&mut self, symbol: Symbol) {
+        match symbol.scope {
+            SymbolScope::Global => self.emit(Instruction::GetGlobal(symbol.index)),
+            SymbolScope::Local => self.emit(Instruction::GetLocal(symbol.index)),
+            SymbolScope::Free => self.emit(Instruction::GetFree(symbol.index)),
+        };
+    }
+
+    fn emit_set(&mut self, symbol: Symbol) {
+        match symbol.scope {
+            SymbolScope::Global => self.emit(Instruction::SetGlobal(symbol.index)),
+            SymbolScope::Local => self.emit(Instruction::SetLocal(symbol.index)),
+            SymbolScope::Free => self.emit(Instruction::SetFree(symbol.index)),
+        };
+    }
+
+    // Stores the value left by `compile_value` into `target`, combined with the target's current
+    // value by `operator` if there is one. The stored value stays on the stack as the result, or
+    // the previous one when `keep_previous` is set.
+    fn compile_assignment(
+        &mut self,
+        target: &Expression,
+        operator: Option<Token>,
+        compile_value: &dyn Fn(&mut Self) -> CompilerResult,
+        keep_previous: bool,
+    ) -> CompilerResult {
+        if let ExpressionKind::Ident(identifier) = &target.kind {
+            if operator.is_some() {
+                self.compile_identifier(identifier);
+            }
+            if keep_previous {
+                self.emit(Instruction::Dup);
+            }
+            self.compile_operation(operator, compile_value)?;
+
+            let symbol = self.resolve_assignable(identifier);
+            self.emit_assign(symbol);
+            if keep_previous {
+                self.emit(Instruction::Pop);
+            }
+
+            return Ok(());
+        }
+
+        let (identifier, depth) = self.compile_index_target(target)?;
+        if operator.is_some() {
+            self.emit(Instruction::IndexKeep);
+        }
+        if keep_previous {
+            self.emit(Instruction::Dup);
+        }
+        self.compile_operation(operator, compile_value)?;
+        if !keep_previous {
+            self.emit(Instruction::Dup);
+        }
+
+        let symbol = self.resolve_assignable(identifier);
+        match symbol.scope {
+            SymbolScope::Global => self.emit(Instruction::SetIndexGlobal(symbol.index, depth)),
+            SymbolScope::Local => self.emit(Instruction::SetIndexLocal(symbol.index, depth)),
+            SymbolScope::Free => self.emit(Instruction::SetIndexFree(symbol.index, depth)),
+        };
+
+        Ok(())
+    }
+
+    // Leaves the root container on the stack, then the index and the nested container of every
+    // level but the last, then the last index. `SetIndex*` stores through the indexes into the
+    // variable itself, so that unshared containers are updated in place.
+    fn compile_index_target<'a>(&mut self, target: &'a Expression) -> Result<(&'a str, usize), CompilerError> {
+        match &target.kind {
+            ExpressionKind::Ident(identifier) => {
+                self.compile_expression(target)?;
+                Ok((identifier, 0))
+            }
+            ExpressionKind::Index(left, index) => {
+                let (identifier, depth) = self.compile_index_target(left)?;
+                if depth > 0 {
+                    self.emit(Instruction::IndexKeep);
+                }
+                self.compile_expression(index)?;
+                Ok((identifier, depth + 1))
+            }
+            _ => Err(CompilerError::new(
+                format!("Invalid assignment target: {}", target),
+                self.span,
+            )),
+        }
+    }
+
+    fn compile_operation(
+        &mut self,
+        operator: Option<Token>,
+        compile_value: &dyn Fn(&mut Self) -> CompilerResult,
+    ) -> CompilerResult {
+        compile_value(self)?;
+
+        if let Some(operator) = operator {
+            match Instruction::from_infix_operator(&operator) {
+                Some(instruction) => self.emit(instruction),
+                None => return Err(CompilerError::new(format!("Unknown operator: {}", operator), self.span)),
+            };
+        }
+
+        Ok(())
+    }
+
+    fn compile_one(&mut self) -> CompilerResult {
+        let index = self.add_constant(Object::Integer(1));
+        self.emit(Instruction::Constant(index));
+        Ok(())
+    }
+
+    fn resolve_assignable(&mut self, identifier: &str) -> Symbol {
+        match self.symbol_table.resolve(identifier) {
+            Some(symbol) => symbol,
+            None => self.symbol_table.define_global(identifier),
+        }
+    }
+
+    fn emit_assign(&mut self, symbol: Symbol) {
+        match symbol.scope {
+            SymbolScope::Global => self.emit(Instruction::AssignGlobal(symbol.index)),
+            SymbolScope::Local => self.emit(Instruction::SetLocal(symbol.index)),
+            SymbolScope::Free => self.emit(Instruction::SetFree(symbol.index)),
+        };
+    }
+
+    // A local defined for the first time in its block gets a fresh cell each time the definition
+    // runs, so closures created in different loop iterations don't share it.
+    fn define_symbol(&mut self, identifier: &str) -> Symbol {
+        let is_new = !self.symbol_table.is_defined_in_current_block(identifier);
+        let symbol = self.symbol_table.define(identifier);
+
+        if is_new && symbol.scope == SymbolScope::Local {
+            self.emit(Instruction::NewLocal(symbol.index));
+        }
+
+        symbol
+    }
+
+    fn add_constant(&mut self, object: Object) -> usize {
+        self.constants.push(Rc::new(object));
+        self.constants.len() - 1
+    }
+
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        let span = self.span;
+        let scope = self.current_scope();
+        scope.instructions.push(instruction);
+        scope.spans.push(span);
+        scope.instructions.len() - 1
+    }
+
+    fn replace_instruction(&mut self, position: usize, instruction: Instruction) {
+        self.current_scope().instructions[position] = instruction;
+    }
+
+    fn current_position(&self) -> usize {
+        self.scopes.last().map_or(0, |scope| scope.instructions.len())
+    }
+
+    fn current_scope(&mut self) -> &mut CompilationScope {
+        self.scopes.last_mut().expect("Compiler has no active scope")
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(CompilationScope::default());
+
+        let outer = mem::take(&mut self.symbol_table);
+        self.symbol_table = SymbolTable::new_enclosed_symbol_table(outer);
+    }
+
+    fn leave_scope(&mut self) -> (CompilationScope, usize, Vec<Symbol>) {
+        let scope = self.scopes.pop().unwrap_or_default();
+
+        let inner = mem::take(&mut self.symbol_table);
+        let num_locals = inner.num_definitions();
+        let captures = inner.free_symbols().to_vec();
+        self.symbol_table = inner.into_outer().unwrap_or_default();
+
+        (scope, num_locals, captures)
+    }
+}
+
+// A call whose result the function returns straight away, directly or through forward jumps,
+// reuses the caller's frame. A call inside a `try` block is followed by `PopHandler` instead.
+fn mark_tail_calls(instructions: &mut Instructions) {
+    for position in 0..instructions.len() {
+        if let Instruction::Call(num_args) = instructions[position] {
+            if returns_from(instructions, position + 1) {
+                instructions[position] = Instruction::TailCall(num_args);
+            }
+        }
+    }
+}
+
+fn returns_from(instructions: &[Instruction], mut position: usize) -> bool {
+    loop {
+        match instructions.get(position) {
+            Some(Instruction::ReturnValue) => return true,
+            Some(Instruction::Jump(target)) if *target > position => position = *target,
+            _ => return false,
+        }
+    }
+}
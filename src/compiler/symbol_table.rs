@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolScope {
+    Global,
+    Local,
+    Free,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Symbol {
+    pub scope: SymbolScope,
+    pub index: usize,
+}
+
+#[derive(Debug)]
+pub struct SymbolTable {
+    blocks: Vec<HashMap<String, Symbol>>,
+    num_definitions: usize,
+    num_globals: usize,
+    free_symbols: Vec<Symbol>,
+    outer: Option<Box<SymbolTable>>,
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        SymbolTable {
+            blocks: vec![HashMap::new()],
+            num_definitions: 0,
+            num_globals: 0,
+            free_symbols: vec![],
+            outer: None,
+        }
+    }
+}
+
+impl SymbolTable {
+    pub fn new_enclosed_symbol_table(outer: SymbolTable) -> Self {
+        SymbolTable {
+            outer: Some(Box::new(outer)),
+            ..Default::default()
+        }
+    }
+
+    pub fn into_outer(self) -> Option<SymbolTable> {
+        self.outer.map(|outer| *outer)
+    }
+
+    pub fn enter_block(&mut self) {
+        self.blocks.push(HashMap::new());
+    }
+
+    pub fn leave_block(&mut self) {
+        if self.blocks.len() > 1 {
+            self.blocks.pop();
+        }
+    }
+
+    pub fn leave_all_blocks(&mut self) {
+        self.blocks.truncate(1);
+    }
+
+    pub fn num_definitions(&self) -> usize {
+        self.num_definitions
+    }
+
+    pub fn free_symbols(&self) -> &[Symbol] {
+        &self.free_symbols
+    }
+
+    pub fn is_defined_in_current_block(&self, name: &str) -> bool {
+        match self.blocks.last().and_then(|block| block.get(name)) {
+            Some(symbol) => symbol.scope != SymbolScope::Free,
+            None => false,
+        }
+    }
+
+    pub fn current_block_locals(&self) -> Vec<Symbol> {
+        let mut locals = self
+            .blocks
+            .last()
+            .into_iter()
+            .flat_map(|block| block.values())
+            .filter(|symbol| symbol.scope == SymbolScope::Local)
+            .copied()
+            .collect::<Vec<Symbol>>();
+        locals.sort_by_key(|symbol| symbol.index);
+        locals
+    }
+
+    pub fn define(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.blocks.last().and_then(|block| block.get(name)) {
+            if symbol.scope != SymbolScope::Free {
+                return *symbol;
+            }
+        }
+
+        if self.outer.is_none() && self.blocks.len() == 1 {
+            return self.define_global(name);
+        }
+
+        let symbol = Symbol {
+            scope: SymbolScope::Local,
+            index: self.num_definitions,
+        };
+
+        self.num_definitions += 1;
+        self.current_block().insert(name.to_string(), symbol);
+        symbol
+    }
+
+    pub fn define_global(&mut self, name: &str) -> Symbol {
+        if let Some(outer) = &mut self.outer {
+            return outer.define_global(name);
+        }
+
+        if let Some(symbol) = self.blocks[0].get(name) {
+            return *symbol;
+        }
+
+        let symbol = Symbol {
+            scope: SymbolScope::Global,
+            index: self.num_globals,
+        };
+
+        self.num_globals += 1;
+        self.blocks[0].insert(name.to_string(), symbol);
+        symbol
+    }
+
+    pub fn resolve(&mut self, name: &str) -> Option<Symbol> {
+        if let Some(symbol) = self.blocks.iter().rev().find_map(|block| block.get(name)) {
+            return Some(*symbol);
+        }
+
+        let symbol = self.outer.as_mut()?.resolve(name)?;
+
+        match symbol.scope {
+            SymbolScope::Global => Some(symbol),
+            SymbolScope::Local | SymbolScope::Free => Some(self.define_free(name, symbol)),
+        }
+    }
+
+    pub fn global_names(&self) -> Vec<String> {
+        match &self.outer {
+            Some(outer) => outer.global_names(),
+            None => {
+                let mut names = vec![String::new(); self.num_globals];
+                for (name, symbol) in &self.blocks[0] {
+                    if symbol.scope == SymbolScope::Global {
+                        names[symbol.index] = name.clone();
+                    }
+                }
+                names
+            }
+        }
+    }
+
+    fn current_block(&mut self) -> &mut HashMap<String, Symbol> {
+        self.blocks.last_mut().expect("symbol table has no block")
+    }
+
+    fn define_free(&mut self, name: &str, original: Symbol) -> Symbol {
+        self.free_symbols.push(original);
+
+        let symbol = Symbol {
+            scope: SymbolScope::Free,
+            index: self.free_symbols.len() - 1,
+        };
+
+        self.blocks[0].insert(name.to_string(), symbol);
+        symbol
+    }
+}
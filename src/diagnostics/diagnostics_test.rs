@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod diagnostics_test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        diagnostics::{did_you_mean, ColorMode, Diagnostic},
+        evaluator::{environment::Env, eval},
+        parser::parser_test::parse,
+        token::{Position, Span},
+    };
+
+    fn span(line: usize, start: usize, end: usize) -> Span {
+        Span::new(Position { line, column: start }, Position { line, column: end })
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let source = "let x = 1;\nlet y = x + true;";
+        let diagnostic = Diagnostic::error("Mismatch type: 1 + true".to_string(), Some(span(2, 9, 17)))
+            .with_note("both operands must have the same type".to_string());
+
+        let expected = [
+            "error: Mismatch type: 1 + true",
+            " --> main.limoo:2:9",
+            "  |",
+            "2 | let y = x + true;",
+            "  |         ^^^^^^^^",
+            "  = note: both operands must have the same type",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(diagnostic.render("main.limoo", source, ColorMode::Plain), expected);
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let diagnostic = Diagnostic::error("boom".to_string(), Some(span(1, 1, 2)));
+        let rendered = diagnostic.render("<repl>", "x", ColorMode::Ansi);
+
+        assert!(rendered.starts_with("\x1b[1;31merror\x1b[0m\x1b[1m: boom\x1b[0m\n"));
+        assert!(rendered.contains("\x1b[1;31m^\x1b[0m"));
+    }
+
+    #[test]
+    fn test_render_without_span() {
+        let diagnostic = Diagnostic::error("boom".to_string(), None).with_help("try again".to_string());
+
+        assert_eq!(
+            diagnostic.render("<repl>", "", ColorMode::Plain),
+            "error: boom\n  = help: try again\n"
+        );
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let candidates = ["len", "print", "push", "counter"];
+
+        assert_eq!(
+            did_you_mean("lne", candidates.iter().copied()),
+            Some("did you mean `len`?".to_string())
+        );
+        assert_eq!(
+            did_you_mean("countr", candidates.iter().copied()),
+            Some("did you mean `counter`?".to_string())
+        );
+        assert_eq!(did_you_mean("xyz", candidates.iter().copied()), None);
+    }
+
+    #[test]
+    fn test_evaluator_error_diagnostic() {
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        let source = "let counter = 1;\nconter + 1";
+
+        let err = eval(parse(source).unwrap(), &env).expect_err("Expected an evaluator error");
+        let rendered = Diagnostic::from(&err).render("<repl>", source, ColorMode::Plain);
+
+        let expected = [
+            "error: Identifier not found: conter",
+            " --> <repl>:2:1",
+            "  |",
+            "2 | conter + 1",
+            "  | ^^^^^^",
+            "  = help: did you mean `counter`?",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn test_len_of_integer_diagnostic() {
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        let source = "len(100000000000000000000)";
+
+        let err = eval(parse(source).unwrap(), &env).expect_err("Expected an evaluator error");
+        let rendered = Diagnostic::from(&err).render("<repl>", source, ColorMode::Plain);
+
+        let expected = [
+            "error: Argument to `len` not supported, got 100000000000000000000",
+            " --> <repl>:1:1",
+            "  |",
+            "1 | len(100000000000000000000)",
+            "  | ^^^^^^^^^^^^^^^^^^^^^^^^^^",
+            "  = help: use `digits` to count the digits of an integer",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn test_evaluator_error_backtrace() {
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        let source = [
+            "let countdown = fn(n) { if (n == 0) { missing } else { countdown(n - 1) } };",
+            "let start = fn() { countdown(2) };",
+            "fn() { start() }()",
+        ]
+        .join("\n");
+
+        let err = eval(parse(&source).unwrap(), &env).expect_err("Expected an evaluator error");
+        let rendered = Diagnostic::from(&err).render("<repl>", &source, ColorMode::Plain);
+
+        let expected = [
+            "error: Identifier not found: missing",
+            " --> <repl>:1:39",
+            "  |",
+            "1 | let countdown = fn(n) { if (n == 0) { missing } else { countdown(n - 1) } };",
+            "  |                                       ^^^^^^^",
+            "  = note: in countdown, called at 1:56 (2 times)",
+            "  = note: in countdown, called at 2:20",
+            "  = note: in start, called at 3:8",
+            "  = note: in <anonymous>, called at 3:1",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn test_parser_error_diagnostic() {
+        let source = "let = 5;";
+        let errors = parse(source).expect_err("Expected a parsing error");
+        let rendered = Diagnostic::from(&errors[0]).render("<repl>", source, ColorMode::Plain);
+
+        let expected = [
+            "error: Expected an identifier but got ASSIGN",
+            " --> <repl>:1:5",
+            "  |",
+            "1 | let = 5;",
+            "  |     ^",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn test_parser_error_at_end_of_input() {
+        let test_case = [
+            ("let x = \n", "1:9", "1 | let x = ", "  |         ^"),
+            ("let x = 1;\nlet y =\r\n\n", "2:8", "2 | let y =", "  |        ^"),
+        ];
+
+        for (source, position, line, underline) in test_case {
+            let errors = parse(source).expect_err("Expected a parsing error");
+            let rendered = Diagnostic::from(&errors[0]).render("<repl>", source, ColorMode::Plain);
+
+            let expected = [
+                "error: no prefix parse function for EOF".to_string(),
+                format!(" --> <repl>:{}", position),
+                "  |".to_string(),
+                line.to_string(),
+                underline.to_string(),
+                "".to_string(),
+            ]
+            .join("\n");
+
+            assert_eq!(rendered, expected, "rendering of: {:?}", source);
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{
+    compiler::error::CompilerError,
+    evaluator::error::{EvaluatorError, StackFrame},
+    parser::error::ParserError,
+    token::{Position, Span},
+};
+
+mod diagnostics_test;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorMode {
+    Plain,
+    Ansi,
+}
+
+const BOLD: &str = "1";
+const RED: &str = "1;31";
+const BLUE: &str = "1;34";
+
+impl ColorMode {
+    fn paint(&self, text: &str, style: &str) -> String {
+        match self {
+            ColorMode::Plain => text.to_string(),
+            ColorMode::Ansi => format!("\x1b[{}m{}\x1b[0m", style, text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Option<Span>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: String, span: Option<Span>) -> Self {
+        Diagnostic {
+            message,
+            span,
+            notes: vec![],
+            help: vec![],
+        }
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Self {
+        self.help.push(help);
+        self
+    }
+
+    pub fn render(&self, file_name: &str, source: &str, mode: ColorMode) -> String {
+        let mut output = format!(
+            "{}{}\n",
+            mode.paint("error", RED),
+            mode.paint(&format!(": {}", self.message), BOLD)
+        );
+
+        let span = self.span.map(|span| clamp_to_source(span, source));
+        let gutter_width = span.map_or(1, |span| span.start.line.to_string().len());
+        let gutter = " ".repeat(gutter_width);
+
+        if let Some(span) = span {
+            output.push_str(&format!(
+                "{}{} {}:{}:{}\n",
+                gutter,
+                mode.paint("-->", BLUE),
+                file_name,
+                span.start.line,
+                span.start.column
+            ));
+
+            if let Some(line) = source.lines().nth(span.start.line.saturating_sub(1)) {
+                let line = line.trim_end_matches('\r');
+                let pipe = mode.paint("|", BLUE);
+
+                output.push_str(&format!("{} {}\n", gutter, pipe));
+                output.push_str(&format!(
+                    "{} {} {}\n",
+                    mode.paint(&span.start.line.to_string(), BLUE),
+                    pipe,
+                    line
+                ));
+                output.push_str(&format!(
+                    "{} {} {}{}\n",
+                    gutter,
+                    pipe,
+                    underline_padding(line, span.start.column),
+                    mode.paint(&"^".repeat(underline_width(line, span)), RED)
+                ));
+            }
+        }
+
+        for note in &self.notes {
+            output.push_str(&format!("{} {} note: {}\n", gutter, mode.paint("=", BLUE), note));
+        }
+        for help in &self.help {
+            output.push_str(&format!("{} {} help: {}\n", gutter, mode.paint("=", BLUE), help));
+        }
+
+        output
+    }
+}
+
+impl From<&ParserError> for Diagnostic {
+    fn from(error: &ParserError) -> Self {
+        Diagnostic::error(error.to_string(), Some(error.span()))
+    }
+}
+
+impl From<&CompilerError> for Diagnostic {
+    fn from(error: &CompilerError) -> Self {
+        Diagnostic::error(error.to_string(), Some(error.span()))
+    }
+}
+
+impl From<&EvaluatorError> for Diagnostic {
+    fn from(error: &EvaluatorError) -> Self {
+        let mut diagnostic = Diagnostic::error(error.to_string(), error.span());
+
+        // Deep recursion repeats the same frame; collapse each run into one note.
+        let stack = error.stack();
+        let mut start = 0;
+        while start < stack.len() {
+            let repeats = stack[start..]
+                .iter()
+                .take_while(|frame| frame.is_same_call(&stack[start]))
+                .count();
+            let frame = StackFrame {
+                times: stack[start..start + repeats].iter().map(|frame| frame.times).sum(),
+                ..stack[start].clone()
+            };
+            diagnostic = diagnostic.with_note(frame.to_string());
+            start += repeats;
+        }
+
+        match error.help() {
+            Some(help) => diagnostic.with_help(help.to_string()),
+            None => diagnostic,
+        }
+    }
+}
+
+pub fn render_all(diagnostics: &[Diagnostic], file_name: &str, source: &str, mode: ColorMode) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.render(file_name, source, mode))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn did_you_mean<'a, I>(name: &str, candidates: I) -> Option<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| format!("did you mean `{}`?", candidate))
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+
+            distances[i][j] = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distances[i][j] = distances[i][j].min(distances[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+// Errors at the end of the input point past its trailing newlines, onto a line with no text;
+// show them at the end of the last line of code instead.
+fn clamp_to_source(span: Span, source: &str) -> Span {
+    let last_line = match source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .last()
+    {
+        Some((index, line)) if span.start.line > index + 1 => (index + 1, line.trim_end_matches('\r')),
+        _ => return span,
+    };
+
+    let end = Position {
+        line: last_line.0,
+        column: last_line.1.chars().count() + 1,
+    };
+    Span::new(end, end)
+}
+
+fn underline_padding(line: &str, column: usize) -> String {
+    line.chars()
+        .take(column.saturating_sub(1))
+        .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+        .collect()
+}
+
+fn underline_width(line: &str, span: Span) -> usize {
+    let line_length = line.chars().count();
+
+    let width = if span.end.line == span.start.line {
+        span.end.column.saturating_sub(span.start.column)
+    } else {
+        (line_length + 1).saturating_sub(span.start.column)
+    };
+
+    width.max(1)
+}
@@ -1,35 +1,92 @@
 use core::fmt;
 use std::rc::Rc;
 
-use crate::object::Object;
+use crate::{bigint::BigInt, object::Object};
 
-use super::error::EvaluatorError;
+use super::{error::EvaluatorError, hash_key, integer_object};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Builtin {
     Len,
     Print,
     Push,
+    Keys,
+    Values,
+    Has,
+    Delete,
+    Int,
+    Float,
+    Round,
+    Floor,
+    Digits,
 }
 
 impl Builtin {
+    pub const ALL: [Builtin; 12] = [
+        Builtin::Len,
+        Builtin::Print,
+        Builtin::Push,
+        Builtin::Keys,
+        Builtin::Values,
+        Builtin::Has,
+        Builtin::Delete,
+        Builtin::Int,
+        Builtin::Float,
+        Builtin::Round,
+        Builtin::Floor,
+        Builtin::Digits,
+    ];
+
     pub fn lookup(name: &str) -> Option<Object> {
         match name {
             "len" => Some(Object::Builtin(Builtin::Len)),
             "print" => Some(Object::Builtin(Builtin::Print)),
             "push" => Some(Object::Builtin(Builtin::Push)),
+            "keys" => Some(Object::Builtin(Builtin::Keys)),
+            "values" => Some(Object::Builtin(Builtin::Values)),
+            "has" => Some(Object::Builtin(Builtin::Has)),
+            "delete" => Some(Object::Builtin(Builtin::Delete)),
+            "int" => Some(Object::Builtin(Builtin::Int)),
+            "float" => Some(Object::Builtin(Builtin::Float)),
+            "round" => Some(Object::Builtin(Builtin::Round)),
+            "floor" => Some(Object::Builtin(Builtin::Floor)),
+            "digits" => Some(Object::Builtin(Builtin::Digits)),
             _ => None,
         }
     }
 
+    pub fn name(&self) -> &'static str {
+        match self {
+            Builtin::Len => "len",
+            Builtin::Print => "print",
+            Builtin::Push => "push",
+            Builtin::Keys => "keys",
+            Builtin::Values => "values",
+            Builtin::Has => "has",
+            Builtin::Delete => "delete",
+            Builtin::Int => "int",
+            Builtin::Float => "float",
+            Builtin::Round => "round",
+            Builtin::Floor => "floor",
+            Builtin::Digits => "digits",
+        }
+    }
+
     pub fn apply(&self, args: &[Rc<Object>]) -> Result<Rc<Object>, EvaluatorError> {
         match self {
             Builtin::Len => {
                 check_argument_count(1, args.len())?;
 
                 match &*args[0] {
-                    Object::String(string) => Ok(Rc::new(Object::Integer(string.len() as i32))),
-                    Object::Array(array) => Ok(Rc::new(Object::Integer(array.len() as i32))),
+                    Object::String(string) => Ok(Rc::new(Object::Integer(string.chars().count() as i64))),
+                    Object::Array(array) => Ok(Rc::new(Object::Integer(array.len() as i64))),
+                    Object::Hash(hash) => Ok(Rc::new(Object::Integer(hash.len() as i64))),
+                    Object::Range(range) => Ok(Rc::new(integer_object(BigInt::from(range.len())))),
+                    object @ (Object::Integer(_) | Object::BigInt(_)) => Err(EvaluatorError::new(format!(
+                        "Argument to `len` not supported, got {}",
+                        object
+                    ))
+                    .with_help(Some("use `digits` to count the digits of an integer".to_string()))),
                     object => Err(EvaluatorError::new(format!(
                         "Argument to `len` not supported, got {}",
                         object
@@ -58,17 +115,136 @@ impl Builtin {
                     ))),
                 }
             }
+            Builtin::Keys => {
+                check_argument_count(1, args.len())?;
+
+                match &*args[0] {
+                    Object::Hash(hash) => Ok(Rc::new(Object::Array(
+                        hash.keys().map(|key| Rc::new(key.to_object())).collect(),
+                    ))),
+                    object => Err(EvaluatorError::new(format!(
+                        "Argument to `keys` not supported, got {}",
+                        object
+                    ))),
+                }
+            }
+            Builtin::Values => {
+                check_argument_count(1, args.len())?;
+
+                match &*args[0] {
+                    Object::Hash(hash) => Ok(Rc::new(Object::Array(hash.values().cloned().collect()))),
+                    object => Err(EvaluatorError::new(format!(
+                        "Argument to `values` not supported, got {}",
+                        object
+                    ))),
+                }
+            }
+            Builtin::Has => {
+                check_argument_count(2, args.len())?;
+
+                match &*args[0] {
+                    Object::Hash(hash) => Ok(Rc::new(Object::Boolean(hash.contains_key(&hash_key(&args[1])?)))),
+                    Object::Range(range) => match *args[1] {
+                        Object::Integer(value) => Ok(Rc::new(Object::Boolean(range.contains(value)))),
+                        _ => Ok(Rc::new(Object::Boolean(false))),
+                    },
+                    object => Err(EvaluatorError::new(format!(
+                        "Argument to `has` not supported, got {}",
+                        object
+                    ))),
+                }
+            }
+            Builtin::Delete => {
+                check_argument_count(2, args.len())?;
+
+                match &*args[0] {
+                    Object::Hash(hash) => {
+                        let mut new_hash = hash.clone();
+                        new_hash.remove(&hash_key(&args[1])?);
+                        Ok(Rc::new(Object::Hash(new_hash)))
+                    }
+                    object => Err(EvaluatorError::new(format!(
+                        "Argument to `delete` not supported, got {}",
+                        object
+                    ))),
+                }
+            }
+            Builtin::Int => {
+                check_argument_count(1, args.len())?;
+
+                match &*args[0] {
+                    Object::Integer(_) | Object::BigInt(_) => Ok(Rc::clone(&args[0])),
+                    Object::Float(f) => Ok(Rc::new(float_to_integer(f.trunc())?)),
+                    Object::String(s) => match BigInt::parse(s.trim()) {
+                        Some(i) => Ok(Rc::new(integer_object(i))),
+                        None => Err(EvaluatorError::new(format!("Could not convert {:?} to int", s))),
+                    },
+                    object => Err(EvaluatorError::new(format!(
+                        "Argument to `int` not supported, got {}",
+                        object
+                    ))),
+                }
+            }
+            Builtin::Float => {
+                check_argument_count(1, args.len())?;
+
+                match &*args[0] {
+                    Object::Integer(i) => Ok(Rc::new(Object::Float(*i as f64))),
+                    Object::BigInt(i) => Ok(Rc::new(Object::Float(i.to_f64()))),
+                    Object::Float(f) => Ok(Rc::new(Object::Float(*f))),
+                    Object::String(s) => match s.trim().parse::<f64>() {
+                        Ok(f) => Ok(Rc::new(Object::Float(f))),
+                        Err(_) => Err(EvaluatorError::new(format!("Could not convert {:?} to float", s))),
+                    },
+                    object => Err(EvaluatorError::new(format!(
+                        "Argument to `float` not supported, got {}",
+                        object
+                    ))),
+                }
+            }
+            Builtin::Round => {
+                check_argument_count(1, args.len())?;
+
+                match &*args[0] {
+                    Object::Integer(_) | Object::BigInt(_) => Ok(Rc::clone(&args[0])),
+                    Object::Float(f) => Ok(Rc::new(float_to_integer(f.round())?)),
+                    object => Err(EvaluatorError::new(format!(
+                        "Argument to `round` not supported, got {}",
+                        object
+                    ))),
+                }
+            }
+            Builtin::Floor => {
+                check_argument_count(1, args.len())?;
+
+                match &*args[0] {
+                    Object::Integer(_) | Object::BigInt(_) => Ok(Rc::clone(&args[0])),
+                    Object::Float(f) => Ok(Rc::new(float_to_integer(f.floor())?)),
+                    object => Err(EvaluatorError::new(format!(
+                        "Argument to `floor` not supported, got {}",
+                        object
+                    ))),
+                }
+            }
+            Builtin::Digits => {
+                check_argument_count(1, args.len())?;
+
+                match &*args[0] {
+                    Object::Integer(i) => Ok(Rc::new(Object::Integer(i.unsigned_abs().to_string().len() as i64))),
+                    Object::BigInt(i) => Ok(Rc::new(Object::Integer(i.num_digits() as i64))),
+                    object => Err(EvaluatorError::new(format!(
+                        "Argument to `digits` not supported, got {}",
+                        object
+                    ))),
+                }
+            }
         }
     }
 }
 
 impl fmt::Display for Builtin {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Builtin::Len => write!(f, "len"),
-            Builtin::Print => write!(f, "print"),
-            Builtin::Push => write!(f, "push"),
-        }
+        write!(f, "{}", self.name())
     }
 }
 
@@ -82,3 +258,10 @@ fn check_argument_count(expected: usize, actual: usize) -> Result<(), EvaluatorE
         Ok(())
     }
 }
+
+fn float_to_integer(float: f64) -> Result<Object, EvaluatorError> {
+    match BigInt::from_f64(float) {
+        Some(integer) => Ok(integer_object(integer)),
+        None => Err(EvaluatorError::new(format!("Cannot convert {:?} to int", float))),
+    }
+}
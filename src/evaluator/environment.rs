@@ -1,21 +1,55 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
 
-use crate::object::Object;
+use crate::{gc, object::Object};
 
 pub type Env = Rc<RefCell<Environment>>;
 
-#[derive(Debug, Clone, Default, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub struct Environment {
     store: HashMap<String, Rc<Object>>,
     outer: Option<Env>,
+    tracked: Cell<bool>,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        gc::environment_created();
+        Environment {
+            store: HashMap::new(),
+            outer: None,
+            tracked: Cell::new(false),
+        }
+    }
+}
+
+impl Clone for Environment {
+    fn clone(&self) -> Self {
+        gc::environment_created();
+        Environment {
+            store: self.store.clone(),
+            outer: self.outer.clone(),
+            tracked: Cell::new(false),
+        }
+    }
+}
+
+impl Drop for Environment {
+    fn drop(&mut self) {
+        gc::environment_dropped();
+    }
 }
 
 impl Environment {
     pub fn new_enclosed_environment(outer: &Env) -> Self {
-        let mut env: Environment = Environment::default();
-        env.outer = Some(Rc::clone(outer));
-        env
+        let mut environment = Environment::default();
+        environment.outer = Some(Rc::clone(outer));
+        environment
     }
+
     pub fn get(&self, name: &str) -> Option<Rc<Object>> {
         match self.store.get(name) {
             Some(object) => Some(Rc::clone(object)),
@@ -29,7 +63,58 @@ impl Environment {
         }
     }
 
+    pub fn names(&self) -> Vec<String> {
+        let mut names = self.store.keys().cloned().collect::<Vec<String>>();
+
+        if let Some(outer) = &self.outer {
+            names.extend(outer.borrow().names());
+        }
+
+        names
+    }
+
     pub fn set(&mut self, name: String, object: Rc<Object>) {
         self.store.insert(name, object);
     }
+
+    pub fn assign(&mut self, name: &str, object: Rc<Object>) -> bool {
+        if let Some(slot) = self.store.get_mut(name) {
+            *slot = object;
+            return true;
+        }
+
+        match &self.outer {
+            Some(outer) => outer.borrow_mut().assign(name, object),
+            None => false,
+        }
+    }
+
+    // Runs `f` on the binding of `name` in the closest environment defining it.
+    pub(crate) fn update<R>(&mut self, name: &str, f: impl FnOnce(&mut Rc<Object>) -> R) -> Option<R> {
+        match self.store.get_mut(name) {
+            Some(slot) => Some(f(slot)),
+            None => match &self.outer {
+                Some(outer) => outer.borrow_mut().update(name, f),
+                None => None,
+            },
+        }
+    }
+
+    pub(crate) fn outer(&self) -> Option<&Env> {
+        self.outer.as_ref()
+    }
+
+    pub(crate) fn values(&self) -> impl Iterator<Item = &Rc<Object>> {
+        self.store.values()
+    }
+
+    // Whether the collector already tracks this environment, marking it tracked from now on.
+    pub(crate) fn mark_tracked(&self) -> bool {
+        self.tracked.replace(true)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.store.clear();
+        self.outer = None;
+    }
 }
@@ -1,16 +1,188 @@
-use std::fmt;
+use std::{collections::VecDeque, fmt, rc::Rc};
 
-#[derive(Debug)]
-pub struct EvaluatorError(String);
+use crate::{object::Object, token::Span};
+
+// Boxed so that results carrying an error stay pointer-sized: the evaluator recurses natively,
+// and every `Result` temporary counts against the native stack of each nested call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluatorError {
+    details: Box<ErrorDetails>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ErrorDetails {
+    message: String,
+    span: Option<Span>,
+    help: Option<String>,
+    value: Option<Rc<Object>>,
+    stack: Vec<StackFrame>,
+}
+
+// How many distinct tail calls a trace keeps before the oldest are only counted.
+const MAX_TRACED_TAIL_CALLS: usize = 16;
+
+const ANONYMOUS: &str = "<anonymous>";
+
+// A function call the error propagated out of: the function's name, where it was called and how
+// many times in a row that call was made.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub function: String,
+    pub call_site: Option<Span>,
+    pub times: usize,
+}
+
+impl StackFrame {
+    pub fn new(function: Option<&str>, call_site: Option<Span>) -> Self {
+        StackFrame {
+            function: function.unwrap_or(ANONYMOUS).to_string(),
+            call_site,
+            times: 1,
+        }
+    }
+
+    fn elided(times: usize) -> Self {
+        StackFrame {
+            function: "<tail calls>".to_string(),
+            call_site: None,
+            times,
+        }
+    }
+
+    pub fn is_same_call(&self, other: &StackFrame) -> bool {
+        self.function == other.function && self.call_site == other.call_site
+    }
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self.call_site {
+            Some(span) => write!(f, "in {}, called at {}", self.function, span)?,
+            None => write!(f, "in {}", self.function)?,
+        }
+        match self.times {
+            1 => Ok(()),
+            times => write!(f, " ({} times)", times),
+        }
+    }
+}
+
+// The calls a running function took the place of through tail calls, oldest first. Repeats of the
+// same call share an entry, and past `MAX_TRACED_TAIL_CALLS` entries the oldest are only counted,
+// so a long tail-recursive loop keeps a bounded trace.
+#[derive(Debug, Default)]
+pub(crate) struct TailCalls {
+    calls: VecDeque<StackFrame>,
+    elided: usize,
+}
+
+impl TailCalls {
+    pub(crate) fn record(&mut self, function: Option<&str>, call_site: Option<Span>) {
+        match self.calls.back_mut() {
+            Some(last) if last.function == function.unwrap_or(ANONYMOUS) && last.call_site == call_site => {
+                last.times += 1
+            }
+            _ => self.calls.push_back(StackFrame::new(function, call_site)),
+        }
+
+        if self.calls.len() > MAX_TRACED_TAIL_CALLS {
+            self.elided += self.calls.pop_front().map_or(0, |call| call.times);
+        }
+    }
+
+    // Adds the replaced calls to the error's stack, innermost first.
+    pub(crate) fn trace(&self, err: EvaluatorError) -> EvaluatorError {
+        let err = self
+            .calls
+            .iter()
+            .rev()
+            .fold(err, |err, call| err.with_frame(call.clone()));
+        match self.elided {
+            0 => err,
+            elided => err.with_frame(StackFrame::elided(elided)),
+        }
+    }
+}
 
 impl fmt::Display for EvaluatorError {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.details.message)
     }
 }
 
 impl EvaluatorError {
-    pub fn new(msg: String) -> Self {
-        EvaluatorError(msg)
+    pub fn new(message: String) -> Self {
+        EvaluatorError {
+            details: Box::new(ErrorDetails {
+                message,
+                span: None,
+                help: None,
+                value: None,
+                stack: vec![],
+            }),
+        }
+    }
+
+    // Rethrowing a caught error keeps its original message and location, but its stack is traced
+    // again from where it was rethrown.
+    pub fn thrown(value: Rc<Object>) -> Self {
+        match &*value {
+            Object::Error(err) => {
+                let mut err = (**err).clone();
+                err.details.stack.clear();
+                err
+            }
+            _ => {
+                let mut err = EvaluatorError::new(value.to_string());
+                err.details.value = Some(Rc::clone(&value));
+                err
+            }
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.details.message
+    }
+
+    // The thrown value, or the message for errors raised by the runtime itself.
+    pub fn value(&self) -> Rc<Object> {
+        match &self.details.value {
+            Some(value) => Rc::clone(value),
+            None => Rc::new(Object::String(self.details.message.clone())),
+        }
+    }
+
+    pub(crate) fn thrown_value(&self) -> Option<&Rc<Object>> {
+        self.details.value.as_ref()
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.details.span
+    }
+
+    pub fn help(&self) -> Option<&str> {
+        self.details.help.as_deref()
+    }
+
+    pub fn with_help(mut self, help: Option<String>) -> Self {
+        self.details.help = help;
+        self
+    }
+
+    // Innermost call first.
+    pub fn stack(&self) -> &[StackFrame] {
+        &self.details.stack
+    }
+
+    pub fn with_frame(mut self, frame: StackFrame) -> Self {
+        self.details.stack.push(frame);
+        self
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        if self.details.span.is_none() {
+            self.details.span = Some(span);
+        }
+        self
     }
 }
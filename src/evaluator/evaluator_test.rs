@@ -3,26 +3,62 @@ mod evaluator_test {
     use std::{cell::RefCell, rc::Rc};
 
     use crate::{
-        evaluator::{environment::Env, eval},
+        ast::Node,
+        compiler::Compiler,
+        evaluator::{environment::Env, eval, set_max_call_depth, DEFAULT_MAX_CALL_DEPTH},
         parser::parser_test::parse,
+        vm::Vm,
     };
 
     fn test_runner(test_case: &[(&str, &str)]) {
         let env: Env = Rc::new(RefCell::new(Default::default()));
+        let mut compiler = Compiler::default();
+        let mut vm = Vm::default();
 
         for (input, expected) in test_case {
-            match parse(input) {
-                Ok(node) => match eval(node, &Rc::clone(&env)) {
-                    Ok(actual) => assert_eq!(expected, &format!("{}", actual)),
-                    Err(err) => assert_eq!(expected, &format!("{}", err)),
+            match eval(parse_input(input, expected), &Rc::clone(&env)) {
+                Ok(actual) => assert_eq!(expected, &format!("{}", actual), "evaluator: {}", input),
+                Err(err) => assert_eq!(expected, &format!("{}", err), "evaluator: {}", input),
+            }
+
+            match compiler.compile(parse_input(input, expected)) {
+                Ok(bytecode) => match vm.run(bytecode) {
+                    Ok(actual) => assert_eq!(expected, &format!("{}", actual), "vm: {}", input),
+                    Err(err) => assert_eq!(expected, &format!("{}", err), "vm: {}", input),
                 },
-                Err(err) => {
-                    println!(
-                        "for input: {} the expected was: {}, which didn't passed!",
-                        input, expected
-                    );
-                    panic!("Parsing Error: {:#?}", err)
-                }
+                Err(err) => assert_eq!(expected, &format!("{}", err), "compiler: {}", input),
+            }
+        }
+    }
+
+    fn error_span_runner(test_case: &[(&str, &str)]) {
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        let mut compiler = Compiler::default();
+        let mut vm = Vm::default();
+
+        for (input, expected) in test_case {
+            match eval(parse_input(input, expected), &Rc::clone(&env)) {
+                Ok(actual) => panic!("evaluator: expected an error for {}, got {}", input, actual),
+                Err(err) => assert_eq!(Some(expected.to_string()), err.span().map(|s| s.to_string())),
+            }
+
+            let bytecode = compiler.compile(parse_input(input, expected)).expect("Compiler Error");
+            match vm.run(bytecode) {
+                Ok(actual) => panic!("vm: expected an error for {}, got {}", input, actual),
+                Err(err) => assert_eq!(Some(expected.to_string()), err.span().map(|s| s.to_string())),
+            }
+        }
+    }
+
+    fn parse_input(input: &str, expected: &str) -> Node {
+        match parse(input) {
+            Ok(node) => node,
+            Err(err) => {
+                println!(
+                    "for input: {} the expected was: {}, which didn't passed!",
+                    input, expected
+                );
+                panic!("Parsing Error: {:#?}", err)
             }
         }
     }
@@ -77,14 +113,204 @@ mod evaluator_test {
             ("true || true", "true"),
             ("false || true", "true"),
             ("false || false", "false"),
-            ("true && 0", "false"),
-            ("true && 1", "true"),
+            ("true && 0", "0"),
+            ("true && 1", "1"),
             ("true || 0", "true"),
             ("true || 1", "true"),
         ];
         test_runner(&tests);
     }
 
+    #[test]
+    fn test_assignment_scoping() {
+        let tests = [
+            (
+                "let make_counter = fn() { let count = 0; fn() { count = count + 1 } };
+                 let counter = make_counter();
+                 counter(); counter(); counter()",
+                "3",
+            ),
+            (
+                "let total = 0; let add = fn(n) { total = total + n }; add(5); add(7); total",
+                "12",
+            ),
+            ("let i = 0; while (i < 3) { i = i + 1 }; i", "3"),
+            ("undeclared = 1", "Cannot assign to undeclared variable: undeclared"),
+            (
+                "let f = fn() { missing = 1 }; f()",
+                "Cannot assign to undeclared variable: missing",
+            ),
+            (
+                "let shadow = 1; let g = fn() { let shadow = 2; shadow = 3; shadow }; g()",
+                "3",
+            ),
+            ("shadow", "1"),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_index_assignment() {
+        let tests = [
+            ("let arr = [1, 2, 3]; arr[0] = 10; arr", "[10, 2, 3]"),
+            ("arr[-1] = 30; arr", "[10, 2, 30]"),
+            ("arr[1] = arr[1] * 2", "4"),
+            ("let grid = [[0, 0], [0, 0]]; grid[1][0] = 5; grid", "[[0, 0], [5, 0]]"),
+            ("let map = {\"a\": 1}; map[\"b\"] = 2; map[\"a\"] = 3; map", "{a: 3, b: 2}"),
+            ("let nested = {\"xs\": [1, 2]}; nested[\"xs\"][0] = 9; nested", "{xs: [9, 2]}"),
+            ("let aliased = [1, 2]; let copy = aliased; copy[0] = 100; aliased", "[1, 2]"),
+            ("copy", "[100, 2]"),
+            (
+                "let inner = [1]; let outer = [inner]; outer[0][0] = 2; [inner, outer]",
+                "[[1], [[2]]]",
+            ),
+            (
+                "let set_first = fn(xs) { xs[0] = 0; xs }; let original = [1, 2]; [set_first(original), original]",
+                "[[0, 2], [1, 2]]",
+            ),
+            (
+                "let squares = [0, 0, 0, 0]; for (i in 0..4) { squares[i] = i * i }; squares",
+                "[0, 1, 4, 9]",
+            ),
+            (
+                "let counts = {}; for (c in \"abca\") { counts[c] = if (has(counts, c)) { counts[c] + 1 } else { 1 } }; counts[\"a\"]",
+                "2",
+            ),
+            ("let short = [1]; short[1] = 2", "Index out of bounds: 1 for array of length 1"),
+            ("short[\"0\"] = 2", "Index must be an integer, got 0"),
+            ("let text = \"abc\"; text[0] = \"x\"", "Index assignment not supported: abc 0"),
+            ("missing_array[0] = 1", "Identifier not found: missing_array"),
+            ("let deep = {}; deep[\"a\"][\"b\"] = 1", "Index assignment not supported: null b"),
+            ("let safe = [[1]]; try { safe[0][3] = 2 } catch (e) { safe }", "[[1]]"),
+            (
+                "let cells = [[0], [0]]; let row = cells[1]; cells[1][0] = 7; [cells, row]",
+                "[[[0], [7]], [0]]",
+            ),
+            (
+                "let boxed = fn() { let slots = [0, 0]; fn(v) { slots[1] = v; slots } }; let setter = boxed(); setter(4); setter(5)",
+                "[0, 5]",
+            ),
+            // Unshared containers are updated in place, so filling one takes linear time.
+            ("let table = {}; for (i in 0..20000) { table[i] = i }; len(table)", "20000"),
+            (
+                "let doubled = values(table); for (i in 0..20000) { doubled[i] = doubled[i] * 2 }; doubled[19999]",
+                "39998",
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        let tests = [
+            ("let total = 10; total += 5", "15"),
+            ("total -= 3; total", "12"),
+            ("total *= 2; total", "24"),
+            ("total /= 5; total", "4"),
+            ("total %= 3; total", "1"),
+            ("let label = \"a\"; label += \"b\"; label", "ab"),
+            ("let ratio = 1.5; ratio *= 2; ratio", "3.0"),
+            ("let big = 9223372036854775807; big += 1; big", "9223372036854775808"),
+            ("let counter = 0; counter++", "0"),
+            ("counter", "1"),
+            ("++counter", "2"),
+            ("counter--", "2"),
+            ("--counter", "0"),
+            ("let sum = 0; for (let i = 0; i < 5; i++) { sum += i }; sum", "10"),
+            ("let values = [1, 2, 3]; values[1] += 10; values", "[1, 12, 3]"),
+            ("values[-1]++; values", "[1, 12, 4]"),
+            ("let tally = {\"a\": 1}; tally[\"a\"] *= 5; tally", "{a: 5}"),
+            (
+                "let matrix = [[1, 2], [3, 4]]; matrix[1][0] -= 3; matrix",
+                "[[1, 2], [0, 4]]",
+            ),
+            (
+                "let calls = 0; let next = fn() { calls++ }; let slots = [0, 0]; slots[next()] += 7; [slots, calls]",
+                "[[7, 0], 1]",
+            ),
+            (
+                "let bump = fn() { let local = [1]; local[0]++; local[0] += 1; local }; bump()",
+                "[3]",
+            ),
+            (
+                "let outer = fn() { let n = 0; let inc = fn() { n += 1 }; inc(); inc(); n }; outer()",
+                "2",
+            ),
+            ("let flag = true; flag++", "Mismatch type: true + 1"),
+            ("missing_total += 1", "Identifier not found: missing_total"),
+            ("let zero = 0; zero /= 0", "Division by zero: 0 / 0"),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let tests = [
+            ("let x1 = 2; let x2 = 3; x1 * x2", "6"),
+            ("let π = 3.5; let r2 = 4; π * r2", "14.0"),
+            ("let größe = 10; größe += 1; größe", "11"),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_block_scopes() {
+        let tests = [
+            ("if (true) { let inner = 1; inner }", "1"),
+            ("inner", "Identifier not found: inner"),
+            ("let n = 0; while (n < 2) { let step = 1; n = n + step }; n", "2"),
+            ("step", "Identifier not found: step"),
+            ("let x = 1; if (true) { let x = 2; x }", "2"),
+            ("x", "1"),
+            ("if (true) { x = 5 }; x", "5"),
+            (
+                "let fns = []; let k = 0;
+                 while (k < 3) { let captured = k; fns = push(fns, fn() { captured }); k = k + 1 };
+                 fns[0]() + fns[1]() * 10 + fns[2]() * 100",
+                "210",
+            ),
+            (
+                "let h = fn() { let out = 0; if (true) { let tmp = 4; out = tmp }; out }; h()",
+                "4",
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_short_circuit_evaluation() {
+        let tests = [
+            ("0 && 1", "0"),
+            ("\"\" || \"default\"", "default"),
+            ("\"set\" || \"default\"", "set"),
+            ("let nothing = if (false) { 1 }; nothing && 1", "null"),
+            ("1 && 2 && 3", "3"),
+            ("0 || nothing || false", "false"),
+            ("1 && 0 || 5", "5"),
+            ("let boom = fn() { 1 / 0 }; false && boom()", "false"),
+            ("true || boom()", "true"),
+            ("true && boom()", "Division by zero: 1 / 0"),
+            ("let xs = []; len(xs) > 0 && xs[0] > 1", "false"),
+            (
+                "let calls = 0; let hit = fn() { true }; true || (calls = calls + 1); calls",
+                "0",
+            ),
+            ("false || (calls = calls + 1); calls", "1"),
+            ("false && (calls = calls + 1); calls", "1"),
+            ("hit() && (calls = calls + 1); calls", "2"),
+            ("if (0 || \"yes\") { 1 } else { 2 }", "1"),
+            ("1 == 1 && 2 == 2", "true"),
+            ("1 < 2 || 3 > 4 && 5 == 6", "true"),
+            ("1 > 2 || 3 < 4 && 5 != 6", "true"),
+            ("let firsts = [2]; len(firsts) != 0 && firsts[0] > 1", "true"),
+            ("let missing = []; len(missing) != 0 && missing[0] > 1", "false"),
+            ("1 == 2 && boom() == 1", "false"),
+            ("1 == 1 || boom() == 1", "true"),
+            ("1 != 1 || 2 == 2 && boom() == 1", "Division by zero: 1 / 0"),
+        ];
+        test_runner(&tests);
+    }
+
     #[test]
     fn test_bang_operator() {
         let tests = [
@@ -117,6 +343,160 @@ mod evaluator_test {
         test_runner(&tests);
     }
 
+    #[test]
+    fn test_break_and_continue() {
+        let tests = [
+            ("let i = 0; while (true) { i = i + 1; if (i == 5) { break; } }; i", "5"),
+            (
+                "let sum = 0; let j = 0;
+                 while (j < 10) { j = j + 1; if (j % 2 == 0) { continue; } sum = sum + j; }
+                 sum",
+                "25",
+            ),
+            (
+                "let pairs = 0; let a = 0;
+                 while (a < 3) {
+                   a = a + 1;
+                   let b = 0;
+                   while (true) { b = b + 1; if (b > a) { break; } pairs = pairs + 1; }
+                 }
+                 pairs",
+                "6",
+            ),
+            (
+                "let find = fn(xs, target) {
+                   let k = 0; let found = -1;
+                   while (k < len(xs)) {
+                     if (xs[k] == target) { found = k; break; }
+                     k = k + 1;
+                   }
+                   found
+                 };
+                 find([4, 8, 15, 16], 15)",
+                "2",
+            ),
+            ("find([4, 8], 1)", "-1"),
+            ("while (true) { if (true) { if (true) { break; } } }", "null"),
+            (
+                "let m = 0; while (m < 3) { m = m + 1; let tmp = [1, 2, if (m < 3) { continue; }]; }; m",
+                "3",
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_c_style_for_loops() {
+        let tests = [
+            (
+                "let total = 0; for (let i = 0; i < 5; i = i + 1) { total = total + i }; total",
+                "10",
+            ),
+            ("i", "Identifier not found: i"),
+            ("let n = 0; for (; n < 3;) { n = n + 1 }; n", "3"),
+            ("let c = 0; for (;;) { c = c + 1; if (c == 4) { break; } }; c", "4"),
+            (
+                "let odd = 0; for (let i = 0; i < 10; i = i + 1) { if (i % 2 == 0) { continue; } odd = odd + i }; odd",
+                "25",
+            ),
+            ("let w = 0; for (w = 10; w > 7; w = w - 1) { }; w", "7"),
+            ("for (let i = 0; i < 3; i = i + 1) { 1 }", "null"),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_for_in_loops() {
+        let tests = [
+            ("let sum = 0; for (x in [1, 2, 3]) { sum = sum + x }; sum", "6"),
+            ("x", "Identifier not found: x"),
+            ("let chars = []; for (ch in \"abc\") { chars = push(chars, ch) }; chars", "[a, b, c]"),
+            ("let keys = []; for (k in {\"b\": 2, \"a\": 1}) { keys = push(keys, k) }; keys", "[a, b]"),
+            ("let seen = []; for (v in [1, 2, 3, 4]) { if (v == 3) { break; } seen = push(seen, v) }; seen", "[1, 2]"),
+            ("let evens = []; for (v in [1, 2, 3, 4]) { if (v % 2 == 1) { continue; } evens = push(evens, v) }; evens", "[2, 4]"),
+            ("let fns = []; for (v in [1, 2, 3]) { fns = push(fns, fn() { v }) }; fns[0]() + fns[2]()", "4"),
+            (
+                "let pairs = []; for (a in [1, 2]) { for (b in \"xy\") { pairs = push(pairs, [a, b]) } }; pairs",
+                "[[1, x], [1, y], [2, x], [2, y]]",
+            ),
+            ("let first = fn(xs) { for (v in xs) { return v; }; -1 }; first([7, 8])", "7"),
+            ("first([])", "-1"),
+            ("for (v in 5) { v }", "Cannot iterate over 5"),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_closures_captured_in_loops() {
+        let tests = [
+            (
+                "let c_style = []; for (let i = 0; i < 3; i = i + 1) { c_style = push(c_style, fn() { i }) }; [c_style[0](), c_style[1](), c_style[2]()]",
+                "[0, 1, 2]",
+            ),
+            (
+                "let for_in = []; for (i in 0..3) { for_in = push(for_in, fn() { i }) }; [for_in[0](), for_in[1](), for_in[2]()]",
+                "[0, 1, 2]",
+            ),
+            (
+                "let collect_c_style = fn() { let fns = []; for (let i = 0; i < 3; i++) { fns = push(fns, fn() { i }) }; [fns[0](), fns[1](), fns[2]()] }; collect_c_style()",
+                "[0, 1, 2]",
+            ),
+            (
+                "let collect_for_in = fn() { let fns = []; for (i in [0, 1, 2]) { fns = push(fns, fn() { i }) }; [fns[0](), fns[1](), fns[2]()] }; collect_for_in()",
+                "[0, 1, 2]",
+            ),
+            (
+                "let skipped = []; for (let i = 0; i < 4; i++) { if (i % 2 == 0) { continue; } skipped = push(skipped, fn() { i }) }; [skipped[0](), skipped[1]()]",
+                "[1, 3]",
+            ),
+            ("let steps = 0; for (let i = 0; i < 10; i++) { i = i + 4; steps = steps + 1 }; steps", "2"),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_ranges() {
+        let tests = [
+            ("0..10", "0..10"),
+            ("1..=3", "1..=3"),
+            ("let n = 4; 0..n * 2", "0..8"),
+            ("len(0..10)", "10"),
+            ("len(0..=10)", "11"),
+            ("len(5..2)", "0"),
+            (
+                "len(-9223372036854775807 - 1..=9223372036854775807)",
+                "18446744073709551616",
+            ),
+            ("let total = 0; for (i in 1..=100) { total = total + i }; total", "5050"),
+            (
+                "let count = 0; for (i in 0..100000000) { if (i == 3) { break; } count = count + 1 }; count",
+                "3",
+            ),
+            ("let down = []; for (i in 3..0) { down = push(down, i) }; down", "[]"),
+            ("has(0..10, 9)", "true"),
+            ("has(0..10, 10)", "false"),
+            ("has(0..=10, 10)", "true"),
+            ("has(0..10, \"a\")", "false"),
+            ("(10..20)[3]", "13"),
+            ("(10..20)[10]", "null"),
+            ("(0..5)[-1]", "4"),
+            ("(0..=5)[-6]", "0"),
+            ("(0..5)[-6]", "null"),
+            ("(0..5)[100000000000000000000]", "null"),
+            ("(0..5)[\"a\"]", "Index must be an integer, got a"),
+            ("[1, 2, 3, 4, 5][1..3]", "[2, 3]"),
+            ("[1, 2, 3, 4, 5][1..=3]", "[2, 3, 4]"),
+            ("[1, 2, 3][2..100]", "[3]"),
+            ("[1, 2, 3][3..1]", "[]"),
+            ("1.5..3", "Range bounds must be integers, got 1.5 .. 3"),
+            (
+                "0..100000000000000000000",
+                "Range bounds must be integers, got 0 .. 100000000000000000000",
+            ),
+        ];
+        test_runner(&tests);
+    }
+
     #[test]
     fn test_return_early_in_loop() {
         let tests = [(
@@ -218,6 +598,13 @@ mod evaluator_test {
             (r#"len("")"#, "0"),
             (r#"len("four")"#, "4"),
             (r#"len("hello world")"#, "11"),
+            (r#"len("héllo")"#, "5"),
+            (r#"len("🍋 limoo")"#, "7"),
+            (r#"let accented = "héllo"; accented[len(accented) - 1]"#, "o"),
+            (
+                r#"let lemon = "🍋🍋"; lemon[len(lemon) - 1] + lemon[0:len(lemon)]"#,
+                "🍋🍋🍋",
+            ),
             ("len(1)", "Argument to `len` not supported, got 1"),
             (r#"len("one", "two")"#, "Invalid number of arguments: expected=1, got=2"),
             ("push([], 1)", "[1]"),
@@ -247,8 +634,439 @@ mod evaluator_test {
             ("let myArray = [1, 2, 3]; myArray[0] + myArray[1] + myArray[2];", "6"),
             ("let myArray = [1, 2, 3]; let i = myArray[0]; myArray[i]", "2"),
             ("[1, 2, 3][3]", "null"),
-            ("[1, 2, 3][-1]", "null"),
+            ("[1, 2, 3][-1]", "3"),
+            ("[1, 2, 3][-3]", "1"),
+            ("[1, 2, 3][-4]", "null"),
+            ("[1, 2, 3][true]", "Index must be an integer, got true"),
+            ("[1, 2, 3][\"0\"]", "Index must be an integer, got 0"),
         ];
         test_runner(&test_case);
     }
+
+    #[test]
+    fn test_string_escapes() {
+        let test_case = [
+            ("len(\"a\\nb\")", "3"),
+            ("\"tab\\there\"", "tab\there"),
+            ("\"quote: \\\"\"", "quote: \""),
+            ("\"\\u{263A}\" == \"☺\"", "true"),
+            ("len(r\"\\n\")", "2"),
+            ("r#\"a \"b\" c\"#", "a \"b\" c"),
+            ("\"first\nsecond\"[5]", "\n"),
+        ];
+        test_runner(&test_case);
+    }
+
+    #[test]
+    fn test_string_interpolation() {
+        let test_case = [
+            (
+                "let name = \"Ada\"; let age = 36; \"hello ${name}, you are ${age + 1}\"",
+                "hello Ada, you are 37",
+            ),
+            ("\"${[1, 2.5, true]} ${{\"k\": 1}}\"", "[1, 2.5, true] {k: 1}"),
+            ("\"${if (false) { 1 }}\"", "null"),
+            ("\"${\"nested ${1 + 1}\"}!\"", "nested 2!"),
+            ("let greet = fn(who) { \"hi ${who}\" }; greet(\"you\")", "hi you"),
+            ("\"total: ${99999999999999999999 + 1}\"", "total: 100000000000000000000"),
+            ("\"cost: \\${5}\"", "cost: ${5}"),
+            ("len(\"${12345}\")", "5"),
+            ("\"${missing}\"", "Identifier not found: missing"),
+        ];
+        test_runner(&test_case);
+    }
+
+    #[test]
+    fn test_string_indexing() {
+        let test_case = [
+            ("\"hello\"[0]", "h"),
+            ("\"hello\"[-1]", "o"),
+            ("\"hello\"[5]", "null"),
+            ("\"héllo wörld\"[7]", "ö"),
+            ("\"日本語\"[1]", "本"),
+            ("\"日本語\"[1..3]", "本語"),
+            ("let s = \"abc\"; s[len(s) - 1]", "c"),
+            ("\"abc\"[1.0]", "Index must be an integer, got 1.0"),
+        ];
+        test_runner(&test_case);
+    }
+
+    #[test]
+    fn test_slice_expression() {
+        let test_case = [
+            ("[1, 2, 3, 4, 5][1:3]", "[2, 3]"),
+            ("[1, 2, 3, 4, 5][:2]", "[1, 2]"),
+            ("[1, 2, 3, 4, 5][3:]", "[4, 5]"),
+            ("[1, 2, 3, 4, 5][:]", "[1, 2, 3, 4, 5]"),
+            ("[1, 2, 3, 4, 5][-2:]", "[4, 5]"),
+            ("[1, 2, 3, 4, 5][:-1]", "[1, 2, 3, 4]"),
+            ("[1, 2, 3][1:100]", "[2, 3]"),
+            ("[1, 2, 3][-100:1]", "[1]"),
+            ("[1, 2, 3][2:1]", "[]"),
+            ("let a = [1, 2, 3]; let b = a[:]; b = push(b, 4); a", "[1, 2, 3]"),
+            ("\"hello world\"[:5]", "hello"),
+            ("\"hello world\"[6:]", "world"),
+            ("\"héllo\"[1:-1]", "éll"),
+            ("let n = 2; \"abcdef\"[n:n * 2]", "cd"),
+            ("[1, 2, 3][100000000000000000000:]", "[]"),
+            ("[1, 2, 3][\"a\":]", "Slice bounds must be integers, got a"),
+            ("5[1:2]", "Slice operator not supported: 5"),
+        ];
+        test_runner(&test_case);
+    }
+
+    #[test]
+    fn test_error_spans() {
+        let test_case = [
+            ("let a = 1;\na + true", "2:1"),
+            ("len(1)", "1:1"),
+            ("let f = fn() { y };\nf()", "1:16"),
+            ("let g = fn(x) { x };\n  g()", "2:3"),
+            ("[1, 2][-true]", "1:8"),
+        ];
+        error_span_runner(&test_case);
+    }
+
+    #[test]
+    fn test_hash_literals() {
+        let test_case = [
+            ("{}", "{}"),
+            (
+                r#"let two = "two"; {"one": 10 - 9, two: 1 + 1, "thr" + "ee": 6 / 2, 4: 4, true: 5, false: 6}"#,
+                "{4: 4, false: 6, true: 5, one: 1, three: 3, two: 2}",
+            ),
+            (r#"{"a": 1, "a": 2}"#, "{a: 2}"),
+            ("{[1]: 2}", "Unusable as hash key: [1]"),
+        ];
+        test_runner(&test_case);
+    }
+
+    #[test]
+    fn test_hash_index_expression() {
+        let test_case = [
+            (r#"{"foo": 5}["foo"]"#, "5"),
+            (r#"{"foo": 5}["bar"]"#, "null"),
+            (r#"let key = "foo"; {"foo": 5}[key]"#, "5"),
+            (r#"{}["foo"]"#, "null"),
+            ("{5: 5}[5]", "5"),
+            ("{true: 5}[true]", "5"),
+            ("{false: 5}[false]", "5"),
+            (r#"{"name": "Limoo"}[fn(x) { x }]"#, "Unusable as hash key: fn(x) {...}"),
+        ];
+        test_runner(&test_case);
+    }
+
+    #[test]
+    fn test_hash_builtins() {
+        let test_case = [
+            (r#"let h = {"b": 2, "a": 1}; keys(h)"#, "[a, b]"),
+            ("values(h)", "[1, 2]"),
+            (r#"has(h, "a")"#, "true"),
+            (r#"has(h, "c")"#, "false"),
+            (r#"delete(h, "a")"#, "{b: 2}"),
+            ("h", "{a: 1, b: 2}"),
+            ("len(h)", "2"),
+            ("keys([])", "Argument to `keys` not supported, got []"),
+            ("has(h, [])", "Unusable as hash key: []"),
+            ("delete(h)", "Invalid number of arguments: expected=2, got=1"),
+        ];
+        test_runner(&test_case);
+    }
+
+    #[test]
+    fn test_float_expression() {
+        let tests = [
+            ("2.75", "2.75"),
+            ("1e-3", "0.001"),
+            ("-2.5", "-2.5"),
+            ("1.5 + 1.5", "3.0"),
+            ("7 / 2", "3"),
+            ("7.0 / 2", "3.5"),
+            ("7 / 2.0", "3.5"),
+            ("0.1 * 3", "0.30000000000000004"),
+            ("2 - 0.5", "1.5"),
+            ("1 < 1.5", "true"),
+            ("2.5 > 3", "false"),
+            ("2 == 2.0", "true"),
+            ("2.0 != 2", "false"),
+            ("if (0.0) { 1 } else { 2 }", "2"),
+            ("1.5 + true", "Mismatch type: 1.5 + true"),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_numeric_conversion_builtins() {
+        let tests = [
+            ("int(3.9)", "3"),
+            ("int(-3.9)", "-3"),
+            (r#"int(" 42 ")"#, "42"),
+            (r#"int("4.2")"#, r#"Could not convert "4.2" to int"#),
+            ("int(1e20)", "100000000000000000000"),
+            ("float(3)", "3.0"),
+            (r#"float("2.5")"#, "2.5"),
+            ("float(true)", "Argument to `float` not supported, got true"),
+            ("round(2.5)", "3"),
+            ("round(-2.4)", "-2"),
+            ("round(7)", "7"),
+            ("floor(2.7)", "2"),
+            ("floor(-2.1)", "-3"),
+            ("{1.5: 1}", "Unusable as hash key: 1.5"),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_integer_promotion() {
+        let tests = [
+            ("2147483647 + 1", "2147483648"),
+            ("9223372036854775807 + 1", "9223372036854775808"),
+            ("-9223372036854775807 - 2", "-9223372036854775809"),
+            ("4611686018427387904 * 2", "9223372036854775808"),
+            ("let min = -9223372036854775807 - 1; min", "-9223372036854775808"),
+            ("-min", "9223372036854775808"),
+            ("min / -1", "9223372036854775808"),
+            ("(9223372036854775807 + 1) - 1", "9223372036854775807"),
+            (
+                "99999999999999999999 * 99999999999999999999",
+                "9999999999999999999800000000000000000001",
+            ),
+            ("-99999999999999999999 / 3", "-33333333333333333333"),
+            (
+                "let fact = fn(n) { if (n < 2) { 1 } else { n * fact(n - 1) } }; fact(30)",
+                "265252859812191058636308480000000",
+            ),
+            ("fact(25) / fact(23)", "600"),
+            ("fact(21) > fact(20)", "true"),
+            ("fact(21) == fact(21)", "true"),
+            ("fact(21) < 5", "false"),
+            ("fact(21) * 0.5", "2.554547108585472e19"),
+            ("1 / 0", "Division by zero: 1 / 0"),
+            ("fact(21) / 0", "Division by zero: 51090942171709440000 / 0"),
+            ("{fact(21): \"big\"}[fact(21)]", "big"),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_modulo_and_division_by_zero() {
+        let tests = [
+            ("10 % 3", "1"),
+            ("-10 % 3", "-1"),
+            ("10 % -3", "1"),
+            ("2 + 7 % 4 * 2", "8"),
+            ("(-9223372036854775807 - 1) % -1", "0"),
+            ("100000000000000000000 % 7", "2"),
+            ("7 % 100000000000000000000", "7"),
+            ("7.5 % 2", "1.5"),
+            ("-7.5 % 2.0", "-1.5"),
+            ("1 / 0", "Division by zero: 1 / 0"),
+            ("1 % 0", "Division by zero: 1 % 0"),
+            (
+                "100000000000000000000 / 0",
+                "Division by zero: 100000000000000000000 / 0",
+            ),
+            (
+                "100000000000000000000 % 0",
+                "Division by zero: 100000000000000000000 % 0",
+            ),
+            ("1.5 / 0.0", "Division by zero: 1.5 / 0.0"),
+            ("1 / 0.0", "Division by zero: 1.0 / 0.0"),
+            ("1.5 % 0", "Division by zero: 1.5 % 0.0"),
+            ("100000000000000000000 / -0.0", "Division by zero: 1e20 / -0.0"),
+            ("let f = fn(a, b) { a / b }; f(4, 2)", "2"),
+            ("f(4, 0)", "Division by zero: 4 / 0"),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_bigint_builtins() {
+        let tests = [
+            ("digits(0)", "1"),
+            ("digits(-12345)", "5"),
+            ("digits(99999999999999999999 + 1)", "21"),
+            ("digits(-9223372036854775807 - 1)", "19"),
+            ("digits(1.5)", "Argument to `digits` not supported, got 1.5"),
+            (
+                "int(\"123456789012345678901234567890\")",
+                "123456789012345678901234567890",
+            ),
+            ("int(\"-42\")", "-42"),
+            ("int(-1e20)", "-100000000000000000000"),
+            ("int(float(\"nan\"))", "Cannot convert NaN to int"),
+            ("float(100000000000000000000)", "1e20"),
+            ("floor(100000000000000000000)", "100000000000000000000"),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_exceptions() {
+        let tests = [
+            (r#"try { throw "boom"; 1 } catch (e) { e["message"] }"#, "boom"),
+            ("try { throw [1, 2]; } catch (e) { e[\"value\"][1] }", "2"),
+            ("try { 1 + true } catch (e) { e[\"message\"] }", "Mismatch type: 1 + true"),
+            ("try { [1][\"a\"] } catch (e) { e }", "Error: Index must be an integer, got a"),
+            ("try { missing } catch (e) { e[\"value\"] }", "Identifier not found: missing"),
+            ("try { 10 } catch (e) { 20 }", "10"),
+            ("let log = []; try { log = push(log, 1); } finally { log = push(log, 2); }; log", "[1, 2]"),
+            (
+                "let trace = []; try { try { throw 1; } finally { trace = push(trace, \"inner\"); } } catch (e) { trace = push(trace, e[\"value\"]); }; trace",
+                "[inner, 1]",
+            ),
+            (
+                "try { try { throw \"first\"; } catch (e) { throw e; } } catch (e) { e[\"message\"] }",
+                "first",
+            ),
+            ("throw \"uncaught\";", "uncaught"),
+            ("try { throw 1; } finally { 2 }", "1"),
+            (
+                "let thrower = fn(x) { if (x > 2) { throw \"too big: ${x}\"; } x };
+                 let safe = fn(x) { try { thrower(x) } catch (e) { -1 } };
+                 [safe(1), safe(5)]",
+                "[1, -1]",
+            ),
+            (
+                "let early = fn() { let cleanup = 0; try { return 1; } finally { cleanup = 1; } };
+                 early()",
+                "1",
+            ),
+            ("let overrides = fn() { try { return 1; } finally { return 2; } }; overrides()", "2"),
+            (
+                "let runs = 0; let k = 0;
+                 while (k < 5) {
+                   k = k + 1;
+                   try { if (k == 2) { continue; } if (k == 4) { break; } } finally { runs = runs + 1; }
+                 };
+                 [k, runs]",
+                "[4, 4]",
+            ),
+            (
+                "let caught = 0;
+                 for (x in [1, 2, 3]) { try { if (x == 2) { throw x; } } catch (e) { caught = caught + e[\"value\"]; } };
+                 caught",
+                "2",
+            ),
+            (
+                "let errorAt = try {\n  1;\n  throw \"x\";\n} catch (e) { [e[\"line\"], e[\"column\"], e[\"other\"]] }; errorAt",
+                "[3, 3, null]",
+            ),
+            ("let e = 5; try { throw 1; } catch (e) { e }; e", "5"),
+            (
+                "let inner = fn() { throw \"deep\"; };\nlet outer = fn() {\n  inner()\n};\ntry { outer() } catch (e) { e[\"stack\"] }",
+                "[in inner, called at 3:3, in outer, called at 5:7]",
+            ),
+            ("try { throw 1; } catch (e) { e[\"stack\"] }", "[]"),
+            (
+                "let fails = fn() { throw \"inner\"; };\nlet rethrows = fn() { try { fails() } catch (e) { throw e; } };\nlet calls = fn() { rethrows() + 1 };\ntry { calls() } catch (e) { [e[\"line\"], e[\"stack\"]] }",
+                "[1, [in rethrows, called at 3:20, in calls, called at 4:7]]",
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_recursion_depth_limit() {
+        set_max_call_depth(50);
+
+        let tests = [
+            (
+                "let runaway = fn(n) { 1 + runaway(n + 1) }; runaway(0)",
+                "Maximum recursion depth exceeded: 50 calls deep",
+            ),
+            (
+                "let depth = fn(n) { if (n == 0) { 0 } else { 1 + depth(n - 1) } }; depth(49)",
+                "49",
+            ),
+            ("depth(50)", "Maximum recursion depth exceeded: 50 calls deep"),
+            ("try { runaway(0) } catch (e) { len(e[\"stack\"]) }", "50"),
+            ("depth(10)", "10"),
+        ];
+        test_runner(&tests);
+
+        set_max_call_depth(DEFAULT_MAX_CALL_DEPTH);
+    }
+
+    #[test]
+    fn test_default_recursion_depth() {
+        // Test threads have small stacks; the evaluator still reaches the default depth on them.
+        let within = (DEFAULT_MAX_CALL_DEPTH - 1).to_string();
+        let exceeded = format!(
+            "Maximum recursion depth exceeded: {} calls deep",
+            DEFAULT_MAX_CALL_DEPTH
+        );
+        let (g_within, g_exceeded) = (format!("g({})", within), format!("g({})", DEFAULT_MAX_CALL_DEPTH));
+        let (t_within, t_exceeded) = (format!("t({})", within), format!("t({})", DEFAULT_MAX_CALL_DEPTH));
+
+        let tests = [
+            ("let g = fn(n) { if (n == 0) { 0 } else { 1 + g(n - 1) } }; 0", "0"),
+            (&g_within, &within),
+            (&g_exceeded, &exceeded),
+            (
+                "let t = fn(n) { if (n == 0) { 0 } else { try { 1 + t(n - 1) } catch (e) { throw e; } } }; 0",
+                "0",
+            ),
+            (&t_within, &within),
+            (&t_exceeded, &exceeded),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_tail_calls() {
+        let tests = [
+            (
+                "let sum = fn(n, acc) { if (n == 0) { acc } else { sum(n - 1, acc + n) } }; sum(1000000, 0)",
+                "500000500000",
+            ),
+            (
+                "let countUp = fn(i, limit) { if (i == limit) { return i; } return countUp(i + 1, limit); };
+                 countUp(0, 100000)",
+                "100000",
+            ),
+            (
+                "let isEven = fn(n) { if (n == 0) { true } else { isOdd(n - 1) } };
+                 let isOdd = fn(n) { if (n == 0) { false } else { isEven(n - 1) } };
+                 [isEven(100000), isOdd(7)]",
+                "[true, true]",
+            ),
+            (
+                "let hop = fn(n) { if (n == 0) { 0 } else { hop(n - 1) } }; hop(5)",
+                "0",
+            ),
+            ("let wrap = fn(xs) { len(xs) }; wrap([1, 2, 3])", "3"),
+            ("let apply = fn(f, x) { f(x) }; apply(fn(x) { x * 2 }, 21)", "42"),
+            (
+                "let arity = fn(a) { sum(a) }; arity(1)",
+                "Expected 2 arguments but got 1",
+            ),
+            (
+                "let skip = fn(n) { if (n == 0) { throw \"done\"; } skip(n - 1) };\ntry { skip(2) } catch (e) { e[\"stack\"] }",
+                "[in skip, called at 1:50, in skip, called at 1:50, in skip, called at 2:7]",
+            ),
+            (
+                "try { skip(100000) } catch (e) { e[\"stack\"] }",
+                "[in skip, called at 1:50, in skip, called at 1:50 (99999 times), in skip, called at 1:7]",
+            ),
+            // Alternating calls can't share entries, so only the latest ones are kept.
+            (
+                "let ping = fn(n) { if (n == 0) { throw \"done\"; } pong(n - 1) };
+                 let pong = fn(n) { ping(n - 1) };
+                 try { ping(100000) } catch (e) { [len(e[\"stack\"]), e[\"stack\"][0], e[\"stack\"][1], e[\"stack\"][17]] }",
+                "[18, in ping, called at 2:37, in pong, called at 1:50, in <tail calls> (99984 times)]",
+            ),
+        ];
+        test_runner(&tests);
+
+        // Replaced calls take no stack, so they don't count towards the call depth.
+        set_max_call_depth(50);
+        let tests = [(
+            "let spin = fn(n) { if (n == 100000) { n } else { spin(n + 1) } }; spin(0)",
+            "100000",
+        )];
+        test_runner(&tests);
+
+        set_max_call_depth(DEFAULT_MAX_CALL_DEPTH);
+    }
 }
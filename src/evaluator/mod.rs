@@ -1,30 +1,103 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fmt,
+    rc::Rc,
+};
 
 use crate::{
-    ast::{Expression, Literal, Node, Statement},
-    object::Object,
-    token::Token,
+    ast::{BlockStatement, Expression, ExpressionKind, Literal, Node, Statement, StatementKind},
+    bigint::BigInt,
+    diagnostics::did_you_mean,
+    gc,
+    object::{HashKey, Object, Range},
+    token::{Span, Token},
 };
 
 use self::{
     builtins::Builtin,
     environment::{Env, Environment},
-    error::EvaluatorError,
+    error::{EvaluatorError, StackFrame, TailCalls},
 };
 
 pub mod builtins;
 pub mod environment;
-mod error;
+pub mod error;
 mod evaluator_test;
 
 pub type EvaluatorResult = Result<Rc<Object>, EvaluatorError>;
 
-fn is_truthy(obj: &Object) -> bool {
+pub const DEFAULT_MAX_CALL_DEPTH: usize = if cfg!(target_arch = "wasm32") { 250 } else { 1000 };
+
+// Native stacks grow by a new segment once less than the red zone is left, enough for the work
+// done between two calls.
+#[cfg(not(target_arch = "wasm32"))]
+const STACK_RED_ZONE: usize = 128 * 1024;
+#[cfg(not(target_arch = "wasm32"))]
+const STACK_SEGMENT_SIZE: usize = 2 * 1024 * 1024;
+
+// A WASM stack can't grow; calls stop with a recursion error once less than this is left.
+#[cfg(target_arch = "wasm32")]
+const MIN_STACK_LEFT: usize = 64 * 1024;
+
+thread_local! {
+    static MAX_CALL_DEPTH: Cell<usize> = const { Cell::new(DEFAULT_MAX_CALL_DEPTH) };
+    static CALL_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+// Bounds how many Limoo function calls may be active at once, in both the evaluator and the VM.
+// Calls replaced by tail calls don't count, as they take no stack: like a `while` loop, a
+// tail-recursive loop runs for as long as it recurses.
+pub fn set_max_call_depth(depth: usize) {
+    MAX_CALL_DEPTH.with(|max| max.set(depth));
+}
+
+pub(crate) fn check_call_depth(depth: usize) -> Result<(), EvaluatorError> {
+    if depth >= MAX_CALL_DEPTH.with(Cell::get) {
+        return Err(recursion_error(depth));
+    }
+    Ok(())
+}
+
+// The evaluator recurses natively for every call that isn't a tail call. Native stacks are grown
+// on demand, so only the call depth bounds that recursion, on whatever thread it runs.
+#[cfg(not(target_arch = "wasm32"))]
+fn with_stack(eval: impl FnOnce() -> EvaluatorResult) -> EvaluatorResult {
+    stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, eval)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn check_stack_size(_depth: usize) -> Result<(), EvaluatorError> {
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn with_stack(eval: impl FnOnce() -> EvaluatorResult) -> EvaluatorResult {
+    eval()
+}
+
+// Rust links WASM modules with the stack first in memory, growing down towards address 0, so the
+// address of a local is how much stack is left below it.
+#[cfg(target_arch = "wasm32")]
+fn check_stack_size(depth: usize) -> Result<(), EvaluatorError> {
+    let marker = 0u8;
+    if (std::hint::black_box(&marker) as *const u8 as usize) < MIN_STACK_LEFT {
+        return Err(recursion_error(depth));
+    }
+    Ok(())
+}
+
+fn recursion_error(depth: usize) -> EvaluatorError {
+    EvaluatorError::new(format!("Maximum recursion depth exceeded: {} calls deep", depth))
+}
+
+pub(crate) fn is_truthy(obj: &Object) -> bool {
     match *obj {
         Object::Null => false,
         Object::Boolean(false) => false,
         Object::String(ref s) if s.is_empty() => false,
         Object::Integer(0) => false,
+        Object::Float(f) if f == 0.0 || f.is_nan() => false,
         _ => true,
     }
 }
@@ -53,79 +126,226 @@ fn eval_program(program: &[Statement], env: &Env) -> EvaluatorResult {
 }
 
 fn eval_statement(statement: &Statement, env: &Env) -> EvaluatorResult {
-    match statement {
-        Statement::Let(identifier, expression) => {
+    match &statement.kind {
+        StatementKind::Let(identifier, expression) => {
             let value = eval_expression(expression, &Rc::clone(env))?;
             let object = Rc::clone(&value);
             env.borrow_mut().set(identifier.clone(), object);
             Ok(value)
         }
-        Statement::Expr(expression) => eval_expression(expression, env),
-        Statement::Return(expression) => {
+        StatementKind::Expr(expression) => eval_expression(expression, env),
+        StatementKind::Return(expression) => {
             let val = eval_expression(expression, env)?;
 
-            return Ok(Rc::new(Object::ReturnValue(val)));
+            Ok(Rc::new(Object::ReturnValue(val)))
+        }
+        StatementKind::Break => Ok(Rc::new(Object::Break)),
+        StatementKind::Continue => Ok(Rc::new(Object::Continue)),
+        StatementKind::Throw(expression) => {
+            let val = eval_expression(expression, env)?;
+
+            Err(EvaluatorError::thrown(val).with_span(statement.span))
         }
     }
 }
 
 fn eval_expression(expression: &Expression, env: &Env) -> EvaluatorResult {
+    eval_expression_kind(&expression.kind, expression.span, env).map_err(|err| err.with_span(expression.span))
+}
+
+fn eval_expression_kind(expression: &ExpressionKind, span: Span, env: &Env) -> EvaluatorResult {
     match expression {
-        Expression::Lit(c) => eval_literal(c, env),
-        Expression::Prefix(operator, expression) => {
+        ExpressionKind::Lit(c) => eval_literal(c, env),
+        ExpressionKind::Prefix(operator, expression) => {
             let right = eval_expression(expression, env)?;
             eval_prefix_expression(operator, &right)
         }
-        Expression::Infix(left, operator, right) => {
+        ExpressionKind::Infix(left, operator @ (Token::AND | Token::OR), right) => {
+            eval_logical_expression(left, operator, right, env)
+        }
+        ExpressionKind::Infix(left, operator, right) => {
             let left = eval_expression(left, &Rc::clone(env))?;
             let right = eval_expression(right, &Rc::clone(env))?;
             eval_infix_expression(&left, operator, &right)
         }
-        Expression::If(condition, consequence, alternative) => {
-            let condition = eval_expression(condition, &Rc::clone(env))?;
-
-            if is_truthy(&condition) {
-                eval_block_statement(consequence, env)
-            } else {
-                match alternative {
-                    Some(alternative) => eval_block_statement(alternative, env),
-                    None => Ok(Rc::new(Object::Null)),
-                }
-            }
+        ExpressionKind::If(condition, consequence, alternative) => {
+            eval_if_expression(condition, consequence, alternative.as_deref(), env)
         }
-        Expression::While(condition, body) => {
-            let mut con = eval_expression(condition, &Rc::clone(env))?;
-            while is_truthy(&con) {
-                let evaluted_body = eval_block_statement(&body, env)?;
-
-                match *evaluted_body {
-                    Object::ReturnValue(_) => return Ok(evaluted_body),
-                    _ => con = eval_expression(condition, &Rc::clone(env))?,
-                }
-            }
-
-            Ok(Rc::new(Object::Null))
+        ExpressionKind::While(condition, body) => eval_while_expression(condition, body, env),
+        ExpressionKind::For(init, condition, update, body) => {
+            eval_for_expression(init.as_deref(), condition.as_deref(), update.as_deref(), body, env)
         }
-        Expression::Ident(identifier) => eval_identifier(identifier, env),
-        Expression::Function(params, body) => {
-            let function = Rc::new(Object::Function(params.clone(), body.clone(), Rc::clone(env)));
-            Ok(function)
+        ExpressionKind::ForIn(variable, iterable, body) => eval_for_in_expression(variable, iterable, body, env),
+        ExpressionKind::Try(body, catch, finally) => eval_try_expression(body, catch.as_ref(), finally.as_deref(), env),
+        ExpressionKind::Ident(identifier) => eval_identifier(identifier, env),
+        ExpressionKind::Function(params, body, name) => {
+            let function = Object::Function(params.clone(), body.clone(), Rc::clone(env), name.clone());
+            gc::track_environment(env);
+            Ok(Rc::new(function))
         }
-        Expression::FunctionCall(function, args) => {
+        ExpressionKind::FunctionCall(function, args) => {
             let func = eval_expression(function, &Rc::clone(env))?;
             let args = eval_expressions(args, env)?;
-            apply_function(&func, &args)
+            apply_function(&func, &args, span)
         }
-        Expression::Assign(identifier, _, expression) => {
-            let value = eval_expression(expression, env)?;
-            env.borrow_mut().set(identifier.clone(), value.clone());
-            Ok(value)
+        ExpressionKind::Assign(target, operator, expression) => {
+            eval_assign_expression(target, operator, expression, env)
         }
-        Expression::Index(left, index) => {
+        ExpressionKind::PrefixUpdate(operator, target) => eval_update_expression(operator, target, true, env),
+        ExpressionKind::PostfixUpdate(target, operator) => eval_update_expression(operator, target, false, env),
+        ExpressionKind::Index(left, index) => {
             let left = eval_expression(left, &Rc::clone(env))?;
             let index = eval_expression(index, &Rc::clone(env))?;
             eval_index_expression(&left, &index)
         }
+        ExpressionKind::Interpolation(parts) => {
+            let parts = eval_expressions(parts, env)?;
+            Ok(eval_interpolation(&parts))
+        }
+        ExpressionKind::Slice(left, start, end) => {
+            let left = eval_expression(left, &Rc::clone(env))?;
+            let start = eval_optional_expression(start.as_deref(), env)?;
+            let end = eval_optional_expression(end.as_deref(), env)?;
+            eval_slice_expression(&left, &start, &end)
+        }
+    }
+}
+
+fn eval_if_expression(
+    condition: &Expression,
+    consequence: &[Statement],
+    alternative: Option<&[Statement]>,
+    env: &Env,
+) -> EvaluatorResult {
+    let condition = eval_expression(condition, &Rc::clone(env))?;
+
+    if is_truthy(&condition) {
+        eval_scoped_block(consequence, env)
+    } else {
+        match alternative {
+            Some(alternative) => eval_scoped_block(alternative, env),
+            None => Ok(Rc::new(Object::Null)),
+        }
+    }
+}
+
+fn eval_while_expression(condition: &Expression, body: &[Statement], env: &Env) -> EvaluatorResult {
+    let mut con = eval_expression(condition, &Rc::clone(env))?;
+    while is_truthy(&con) {
+        let evaluted_body = eval_scoped_block(body, env)?;
+
+        match *evaluted_body {
+            Object::ReturnValue(_) => return Ok(evaluted_body),
+            Object::Break => break,
+            _ => con = eval_expression(condition, &Rc::clone(env))?,
+        }
+    }
+
+    Ok(Rc::new(Object::Null))
+}
+
+fn eval_assign_expression(
+    target: &Expression,
+    operator: &Token,
+    expression: &Expression,
+    env: &Env,
+) -> EvaluatorResult {
+    let mut result = Rc::new(Object::Null);
+    eval_assignment(
+        target,
+        &mut |current| {
+            let value = match operator.binary_operator() {
+                Some(operator) => {
+                    let current = current()?;
+                    let value = eval_expression(expression, &Rc::clone(env))?;
+                    eval_infix_expression(&current, &operator, &value)?
+                }
+                None => eval_expression(expression, &Rc::clone(env))?,
+            };
+            result = Rc::clone(&value);
+            Ok(value)
+        },
+        env,
+    )?;
+    Ok(result)
+}
+
+// Evaluates each part of the target once and stores whatever `update` returns, given a way to
+// read the target's current value. Arrays and hashes are values: assigning through an index
+// copies the containers on the way only when they are shared, so other bindings of the old
+// container never observe the change.
+fn eval_assignment(
+    target: &Expression,
+    update: &mut dyn FnMut(&dyn Fn() -> EvaluatorResult) -> EvaluatorResult,
+    env: &Env,
+) -> Result<(), EvaluatorError> {
+    let (identifier, indexes) = assignment_target(target)?;
+
+    let (value, path) = match indexes.split_last() {
+        None => (update(&|| eval_identifier(identifier, env))?, vec![]),
+        Some((last, indexes)) => {
+            let mut container = eval_identifier(identifier, env)?;
+            let mut path = Vec::with_capacity(indexes.len() + 1);
+            for index in indexes {
+                path.push(eval_expression(index, &Rc::clone(env))?);
+                container = eval_index_expression(&container, &path[path.len() - 1])?;
+            }
+            path.push(eval_expression(last, &Rc::clone(env))?);
+
+            let value = update(&|| eval_index_expression(&container, &path[path.len() - 1]))?;
+            (value, path)
+        }
+    };
+
+    let assigned = env
+        .borrow_mut()
+        .update(identifier, |slot| assign_index(slot, &path, value));
+    match assigned {
+        Some(assigned) => assigned,
+        None => Err(error_undeclared(
+            format!("Cannot assign to undeclared variable: {}", identifier),
+            identifier,
+            env,
+        )),
+    }
+}
+
+// The variable an assignment stores into and the indexes leading to its target, outermost first.
+fn assignment_target(target: &Expression) -> Result<(&str, Vec<&Expression>), EvaluatorError> {
+    match &target.kind {
+        ExpressionKind::Ident(identifier) => Ok((identifier, vec![])),
+        ExpressionKind::Index(left, index) => {
+            let (identifier, mut indexes) = assignment_target(left)?;
+            indexes.push(index);
+            Ok((identifier, indexes))
+        }
+        _ => Err(EvaluatorError::new(format!("Invalid assignment target: {}", target))),
+    }
+}
+
+fn eval_update_expression(operator: &Token, target: &Expression, is_prefix: bool, env: &Env) -> EvaluatorResult {
+    let binary_operator = operator
+        .binary_operator()
+        .ok_or_else(|| EvaluatorError::new(format!("Unknown operator: {}", operator)))?;
+
+    let mut result = Rc::new(Object::Null);
+    eval_assignment(
+        target,
+        &mut |current| {
+            let current = current()?;
+            let value = eval_infix_expression(&current, &binary_operator, &Rc::new(Object::Integer(1)))?;
+            result = if is_prefix { Rc::clone(&value) } else { current };
+            Ok(value)
+        },
+        env,
+    )?;
+    Ok(result)
+}
+
+fn eval_optional_expression(expression: Option<&Expression>, env: &Env) -> EvaluatorResult {
+    match expression {
+        Some(expression) => eval_expression(expression, &Rc::clone(env)),
+        None => Ok(Rc::new(Object::Null)),
     }
 }
 
@@ -143,16 +363,43 @@ fn eval_expressions(expressions: &[Expression], env: &Env) -> Result<Vec<Rc<Obje
 fn eval_literal(literal: &Literal, env: &Env) -> EvaluatorResult {
     match literal {
         Literal::Integer(i) => Ok(Rc::new(Object::Integer(*i))),
+        Literal::BigInt(i) => Ok(Rc::new(Object::BigInt(i.clone()))),
+        Literal::Float(f) => Ok(Rc::new(Object::Float(*f))),
         Literal::Boolean(b) => Ok(Rc::new(Object::Boolean(*b))),
         Literal::String(s) => Ok(Rc::new(Object::String(s.clone()))),
         Literal::Array(arr) => {
-            let list = eval_expressions(&arr, &Rc::clone(env))?;
+            let list = eval_expressions(arr, &Rc::clone(env))?;
             Ok(Rc::new(Object::Array(list)))
         }
+        Literal::Hash(pairs) => {
+            let mut list = Vec::new();
+
+            for (key, value) in pairs {
+                let key = eval_expression(key, &Rc::clone(env))?;
+                let value = eval_expression(value, &Rc::clone(env))?;
+                list.push((key, value));
+            }
+
+            eval_hash_literal(list)
+        }
     }
 }
 
-fn eval_prefix_expression(operator: &Token, right: &Rc<Object>) -> EvaluatorResult {
+pub(crate) fn eval_hash_literal(pairs: Vec<(Rc<Object>, Rc<Object>)>) -> EvaluatorResult {
+    let mut hash = BTreeMap::new();
+
+    for (key, value) in pairs {
+        hash.insert(hash_key(&key)?, value);
+    }
+
+    Ok(Rc::new(Object::Hash(hash)))
+}
+
+pub(crate) fn hash_key(object: &Object) -> Result<HashKey, EvaluatorError> {
+    HashKey::from_object(object).ok_or_else(|| EvaluatorError::new(format!("Unusable as hash key: {}", object)))
+}
+
+pub(crate) fn eval_prefix_expression(operator: &Token, right: &Rc<Object>) -> EvaluatorResult {
     match operator {
         Token::BANG => eval_bang_operator(right),
         Token::MINUS => eval_minus_operator(right),
@@ -170,18 +417,39 @@ fn eval_bang_operator(expression: &Rc<Object>) -> EvaluatorResult {
 
 fn eval_minus_operator(expression: &Rc<Object>) -> EvaluatorResult {
     match **expression {
-        Object::Integer(i) => Ok(Rc::new(Object::Integer(-i))),
+        Object::Integer(i) => match i.checked_neg() {
+            Some(i) => Ok(Rc::new(Object::Integer(i))),
+            None => Ok(Rc::new(integer_object(-&BigInt::from(i)))),
+        },
+        Object::BigInt(ref i) => Ok(Rc::new(integer_object(-i))),
+        Object::Float(f) => Ok(Rc::new(Object::Float(-f))),
         _ => Err(EvaluatorError::new(format!("Unknown operator: -{}", expression))),
     }
 }
 
-fn eval_infix_expression(left: &Rc<Object>, operator: &Token, right: &Rc<Object>) -> EvaluatorResult {
-    if *operator == Token::AND || *operator == Token::OR {
-        return eval_logical_infix_expression(left, operator, right);
+pub(crate) fn eval_infix_expression(left: &Rc<Object>, operator: &Token, right: &Rc<Object>) -> EvaluatorResult {
+    let is_range = *operator == Token::DOTDOT || *operator == Token::DOTDOT_EQ;
+    if is_range && !matches!((&**left, &**right), (Object::Integer(_), Object::Integer(_))) {
+        return Err(EvaluatorError::new(format!(
+            "Range bounds must be integers, got {} {} {}",
+            left, operator, right
+        )));
     }
 
     match (&**left, &**right) {
         (Object::Integer(left), Object::Integer(right)) => eval_integer_infix_expression(*left, operator, *right),
+        (Object::Float(left), Object::Float(right)) => eval_float_infix_expression(*left, operator, *right),
+        (Object::Integer(left), Object::Float(right)) => eval_float_infix_expression(*left as f64, operator, *right),
+        (Object::Float(left), Object::Integer(right)) => eval_float_infix_expression(*left, operator, *right as f64),
+        (Object::BigInt(left), Object::BigInt(right)) => eval_bigint_infix_expression(left, operator, right),
+        (Object::BigInt(left), Object::Integer(right)) => {
+            eval_bigint_infix_expression(left, operator, &BigInt::from(*right))
+        }
+        (Object::Integer(left), Object::BigInt(right)) => {
+            eval_bigint_infix_expression(&BigInt::from(*left), operator, right)
+        }
+        (Object::BigInt(left), Object::Float(right)) => eval_float_infix_expression(left.to_f64(), operator, *right),
+        (Object::Float(left), Object::BigInt(right)) => eval_float_infix_expression(*left, operator, right.to_f64()),
         (Object::Boolean(left), Object::Boolean(right)) => eval_boolean_infix_expression(*left, operator, *right),
         (Object::String(left), Object::String(right)) => eval_string_infix_expression(left, operator, right),
         _ => Err(EvaluatorError::new(format!(
@@ -191,12 +459,41 @@ fn eval_infix_expression(left: &Rc<Object>, operator: &Token, right: &Rc<Object>
     }
 }
 
-fn eval_logical_infix_expression(left: &Rc<Object>, operator: &Token, right: &Rc<Object>) -> EvaluatorResult {
-    let (left_coercion, right_ceorcion) = (is_truthy(left), is_truthy(right));
+fn eval_logical_expression(left: &Expression, operator: &Token, right: &Expression, env: &Env) -> EvaluatorResult {
+    let left = eval_expression(left, &Rc::clone(env))?;
+
+    let is_decided = match operator {
+        Token::AND => !is_truthy(&left),
+        _ => is_truthy(&left),
+    };
+
+    if is_decided {
+        Ok(left)
+    } else {
+        eval_expression(right, &Rc::clone(env))
+    }
+}
+
+fn eval_integer_infix_expression(left: i64, operator: &Token, right: i64) -> EvaluatorResult {
+    if right == 0 && is_division(operator) {
+        return Err(division_by_zero(left, operator, right));
+    }
 
     let result = match operator {
-        Token::AND => left_coercion && right_ceorcion,
-        Token::OR => left_coercion || right_ceorcion,
+        Token::PLUS => left.checked_add(right).map(Object::Integer),
+        Token::MINUS => left.checked_sub(right).map(Object::Integer),
+        Token::ASTERISK => left.checked_mul(right).map(Object::Integer),
+        Token::SLASH => left.checked_div(right).map(Object::Integer),
+        Token::PERCENT => left.checked_rem(right).map(Object::Integer),
+        Token::EQ => Some(Object::Boolean(left == right)),
+        Token::NOT_EQ => Some(Object::Boolean(left != right)),
+        Token::LT => Some(Object::Boolean(left < right)),
+        Token::GT => Some(Object::Boolean(left > right)),
+        Token::DOTDOT | Token::DOTDOT_EQ => Some(Object::Range(Range {
+            start: left,
+            end: right,
+            inclusive: *operator == Token::DOTDOT_EQ,
+        })),
         _ => {
             return Err(EvaluatorError::new(format!(
                 "Unknown operator: {} {} {}",
@@ -205,15 +502,23 @@ fn eval_logical_infix_expression(left: &Rc<Object>, operator: &Token, right: &Rc
         }
     };
 
-    Ok(Rc::new(Object::Boolean(result)))
+    match result {
+        Some(result) => Ok(Rc::new(result)),
+        None => eval_bigint_infix_expression(&BigInt::from(left), operator, &BigInt::from(right)),
+    }
 }
 
-fn eval_integer_infix_expression(left: i32, operator: &Token, right: i32) -> EvaluatorResult {
+fn eval_bigint_infix_expression(left: &BigInt, operator: &Token, right: &BigInt) -> EvaluatorResult {
+    if right.is_zero() && is_division(operator) {
+        return Err(division_by_zero(left, operator, right));
+    }
+
     let result = match operator {
-        Token::PLUS => Object::Integer(left + right),
-        Token::MINUS => Object::Integer(left - right),
-        Token::ASTERISK => Object::Integer(left * right),
-        Token::SLASH => Object::Integer(left / right),
+        Token::PLUS => integer_object(left + right),
+        Token::MINUS => integer_object(left - right),
+        Token::ASTERISK => integer_object(left * right),
+        Token::SLASH => integer_object(left.checked_div(right).unwrap()),
+        Token::PERCENT => integer_object(left.checked_rem(right).unwrap()),
         Token::EQ => Object::Boolean(left == right),
         Token::NOT_EQ => Object::Boolean(left != right),
         Token::LT => Object::Boolean(left < right),
@@ -229,6 +534,47 @@ fn eval_integer_infix_expression(left: i32, operator: &Token, right: i32) -> Eva
     Ok(Rc::new(result))
 }
 
+pub(crate) fn integer_object(integer: BigInt) -> Object {
+    match integer.to_i64() {
+        Some(i) => Object::Integer(i),
+        None => Object::BigInt(integer),
+    }
+}
+
+fn is_division(operator: &Token) -> bool {
+    *operator == Token::SLASH || *operator == Token::PERCENT
+}
+
+fn division_by_zero(left: impl fmt::Display, operator: &Token, right: impl fmt::Display) -> EvaluatorError {
+    EvaluatorError::new(format!("Division by zero: {} {} {}", left, operator, right))
+}
+
+fn eval_float_infix_expression(left: f64, operator: &Token, right: f64) -> EvaluatorResult {
+    if right == 0.0 && is_division(operator) {
+        return Err(division_by_zero(Object::Float(left), operator, Object::Float(right)));
+    }
+
+    let result = match operator {
+        Token::PLUS => Object::Float(left + right),
+        Token::MINUS => Object::Float(left - right),
+        Token::ASTERISK => Object::Float(left * right),
+        Token::SLASH => Object::Float(left / right),
+        Token::PERCENT => Object::Float(left % right),
+        Token::EQ => Object::Boolean(left == right),
+        Token::NOT_EQ => Object::Boolean(left != right),
+        Token::LT => Object::Boolean(left < right),
+        Token::GT => Object::Boolean(left > right),
+        _ => {
+            return Err(EvaluatorError::new(format!(
+                "Unknown operator: {:?} {} {:?}",
+                left, operator, right
+            )))
+        }
+    };
+
+    Ok(Rc::new(result))
+}
+
 fn eval_boolean_infix_expression(left: bool, operator: &Token, right: bool) -> EvaluatorResult {
     let result = match operator {
         Token::EQ => Object::Boolean(left == right),
@@ -260,18 +606,53 @@ fn eval_string_infix_expression(left: &str, operator: &Token, right: &str) -> Ev
     Ok(Rc::new(result))
 }
 
-fn eval_index_expression(left: &Rc<Object>, index: &Rc<Object>) -> EvaluatorResult {
+pub(crate) fn eval_interpolation(parts: &[Rc<Object>]) -> Rc<Object> {
+    let string = parts.iter().map(|part| part.to_string()).collect::<String>();
+    Rc::new(Object::String(string))
+}
+
+pub(crate) fn eval_index_expression(left: &Rc<Object>, index: &Rc<Object>) -> EvaluatorResult {
     match (&**left, &**index) {
-        (Object::Array(arr), Object::Integer(idx)) => {
-            if *idx < 0 {
-                Ok(Rc::new(Object::Null))
+        (Object::Array(arr), Object::Integer(idx)) => match normalize_index(*idx, arr.len()) {
+            Some(idx) => Ok(Rc::clone(&arr[idx])),
+            None => Ok(Rc::new(Object::Null)),
+        },
+        (Object::String(s), Object::Integer(idx)) => {
+            let chars = s.chars().collect::<Vec<char>>();
+            match normalize_index(*idx, chars.len()) {
+                Some(idx) => Ok(Rc::new(Object::String(chars[idx].to_string()))),
+                None => Ok(Rc::new(Object::Null)),
+            }
+        }
+        (Object::Range(range), Object::Integer(idx)) => {
+            let idx = if *idx < 0 {
+                *idx as i128 + range.len()
             } else {
-                match arr.get(*idx as usize) {
-                    Some(val) => Ok(Rc::clone(val)),
-                    None => Ok(Rc::new(Object::Null)),
-                }
+                *idx as i128
+            };
+            match range.get(idx) {
+                Some(val) => Ok(Rc::new(Object::Integer(val))),
+                None => Ok(Rc::new(Object::Null)),
             }
         }
+        (Object::Array(_) | Object::String(_) | Object::Range(_), Object::BigInt(_)) => Ok(Rc::new(Object::Null)),
+        (Object::Array(arr), Object::Range(range)) => {
+            let (start, end) = range_bounds(range, arr.len());
+            Ok(Rc::new(Object::Array(arr[start..end].to_vec())))
+        }
+        (Object::String(s), Object::Range(range)) => {
+            let chars = s.chars().collect::<Vec<char>>();
+            let (start, end) = range_bounds(range, chars.len());
+            Ok(Rc::new(Object::String(chars[start..end].iter().collect())))
+        }
+        (Object::Array(_) | Object::String(_) | Object::Range(_), _) => {
+            Err(EvaluatorError::new(format!("Index must be an integer, got {}", index)))
+        }
+        (Object::Hash(hash), key) => match hash.get(&hash_key(key)?) {
+            Some(val) => Ok(Rc::clone(val)),
+            None => Ok(Rc::new(Object::Null)),
+        },
+        (Object::Error(err), Object::String(field)) => Ok(error_field(err, field)),
         _ => Err(EvaluatorError::new(format!(
             "Index operator not supported: {} {}",
             left, index
@@ -279,6 +660,149 @@ fn eval_index_expression(left: &Rc<Object>, index: &Rc<Object>) -> EvaluatorResu
     }
 }
 
+fn error_field(err: &EvaluatorError, field: &str) -> Rc<Object> {
+    let position = err.span().map(|span| span.start);
+
+    match (field, position) {
+        ("message", _) => Rc::new(Object::String(err.message().to_string())),
+        ("value", _) => err.value(),
+        ("stack", _) => Rc::new(Object::Array(
+            err.stack()
+                .iter()
+                .map(|frame| Rc::new(Object::String(frame.to_string())))
+                .collect(),
+        )),
+        ("line", Some(position)) => Rc::new(Object::Integer(position.line as i64)),
+        ("column", Some(position)) => Rc::new(Object::Integer(position.column as i64)),
+        _ => Rc::new(Object::Null),
+    }
+}
+
+// Stores `value` at the end of `path` inside `container`. Containers on the way are copied only
+// when something else shares them, so updating an unshared array or hash doesn't copy it.
+pub(crate) fn assign_index(
+    container: &mut Rc<Object>,
+    path: &[Rc<Object>],
+    value: Rc<Object>,
+) -> Result<(), EvaluatorError> {
+    let (index, rest) = match path.split_first() {
+        Some(step) => step,
+        None => {
+            *container = value;
+            return Ok(());
+        }
+    };
+
+    match (&**container, &**index) {
+        (Object::Array(arr), Object::Integer(idx)) if normalize_index(*idx, arr.len()).is_some() => {}
+        (Object::Array(arr), Object::Integer(_) | Object::BigInt(_)) => {
+            return Err(EvaluatorError::new(format!(
+                "Index out of bounds: {} for array of length {}",
+                index,
+                arr.len()
+            )))
+        }
+        (Object::Array(_), _) => return Err(EvaluatorError::new(format!("Index must be an integer, got {}", index))),
+        (Object::Hash(_), key) => {
+            hash_key(key)?;
+        }
+        _ => {
+            return Err(EvaluatorError::new(format!(
+                "Index assignment not supported: {} {}",
+                container, index
+            )))
+        }
+    }
+
+    match (Rc::make_mut(container), &**index) {
+        (Object::Array(arr), Object::Integer(idx)) => {
+            let idx = normalize_index(*idx, arr.len()).expect("index was checked");
+            assign_index(&mut arr[idx], rest, value)
+        }
+        (Object::Hash(hash), key) => {
+            let key = hash_key(key)?;
+            match hash.get_mut(&key) {
+                Some(slot) => assign_index(slot, rest, value),
+                None if rest.is_empty() => {
+                    hash.insert(key, value);
+                    Ok(())
+                }
+                // Missing keys read as null, which can't be indexed into.
+                None => assign_index(&mut Rc::new(Object::Null), rest, value),
+            }
+        }
+        _ => unreachable!("index assignment target was checked"),
+    }
+}
+
+// A `Null` bound stands for an omitted one, as in `arr[:2]`.
+pub(crate) fn eval_slice_expression(left: &Rc<Object>, start: &Rc<Object>, end: &Rc<Object>) -> EvaluatorResult {
+    let start = slice_bound(start)?;
+    let end = slice_bound(end)?;
+
+    match &**left {
+        Object::Array(arr) => {
+            let (start, end) = slice_bounds(start, end, arr.len());
+            Ok(Rc::new(Object::Array(arr[start..end].to_vec())))
+        }
+        Object::String(s) => {
+            let chars = s.chars().collect::<Vec<char>>();
+            let (start, end) = slice_bounds(start, end, chars.len());
+            Ok(Rc::new(Object::String(chars[start..end].iter().collect())))
+        }
+        _ => Err(EvaluatorError::new(format!("Slice operator not supported: {}", left))),
+    }
+}
+
+fn normalize_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 {
+        index as i128 + len as i128
+    } else {
+        index as i128
+    };
+
+    if (0..len as i128).contains(&index) {
+        Some(index as usize)
+    } else {
+        None
+    }
+}
+
+fn range_bounds(range: &Range, len: usize) -> (usize, usize) {
+    let start = (range.start as i128).clamp(0, len as i128);
+    let end = (range.end as i128 + i128::from(range.inclusive)).clamp(start, len as i128);
+    (start as usize, end as usize)
+}
+
+fn slice_bound(bound: &Object) -> Result<Option<i64>, EvaluatorError> {
+    match bound {
+        Object::Null => Ok(None),
+        Object::Integer(i) => Ok(Some(*i)),
+        Object::BigInt(i) if *i < BigInt::default() => Ok(Some(i64::MIN)),
+        Object::BigInt(_) => Ok(Some(i64::MAX)),
+        _ => Err(EvaluatorError::new(format!(
+            "Slice bounds must be integers, got {}",
+            bound
+        ))),
+    }
+}
+
+// Negative bounds count from the end, and both bounds are clamped to the sequence.
+fn slice_bounds(start: Option<i64>, end: Option<i64>, len: usize) -> (usize, usize) {
+    let clamp = |bound: i64| {
+        let bound = if bound < 0 {
+            bound as i128 + len as i128
+        } else {
+            bound as i128
+        };
+        bound.clamp(0, len as i128) as usize
+    };
+
+    let start = start.map_or(0, clamp);
+    let end = end.map_or(len, clamp).max(start);
+    (start, end)
+}
+
 fn eval_block_statement(statements: &[Statement], env: &Env) -> EvaluatorResult {
     let mut result = Rc::new(Object::Null);
 
@@ -286,7 +810,135 @@ fn eval_block_statement(statements: &[Statement], env: &Env) -> EvaluatorResult
         let val = eval_statement(statement, &Rc::clone(env))?;
 
         match *val {
-            Object::ReturnValue(_) => return Ok(val),
+            Object::ReturnValue(_) | Object::Break | Object::Continue => return Ok(val),
+            _ => result = val,
+        }
+    }
+
+    Ok(result)
+}
+
+fn eval_for_expression(
+    init: Option<&Statement>,
+    condition: Option<&Expression>,
+    update: Option<&Expression>,
+    body: &[Statement],
+    env: &Env,
+) -> EvaluatorResult {
+    let mut loop_env = Rc::new(RefCell::new(Environment::new_enclosed_environment(env)));
+
+    if let Some(init) = init {
+        eval_statement(init, &loop_env)?;
+    }
+
+    loop {
+        if let Some(condition) = condition {
+            let condition = eval_expression(condition, &loop_env)?;
+            if !is_truthy(&condition) {
+                break;
+            }
+        }
+
+        let evaluted_body = eval_scoped_block(body, &loop_env)?;
+        match *evaluted_body {
+            Object::ReturnValue(_) => return Ok(evaluted_body),
+            Object::Break => break,
+            _ => {}
+        }
+
+        // Like `for-in`, every iteration gets its own bindings: they are copied before the update,
+        // so closures from the body keep the values they saw.
+        let next_env = loop_env.borrow().clone();
+        loop_env = Rc::new(RefCell::new(next_env));
+
+        if let Some(update) = update {
+            eval_expression(update, &loop_env)?;
+        }
+    }
+
+    Ok(Rc::new(Object::Null))
+}
+
+fn eval_for_in_expression(variable: &str, iterable: &Expression, body: &[Statement], env: &Env) -> EvaluatorResult {
+    let iterable = eval_expression(iterable, &Rc::clone(env))?;
+    let items: Box<dyn Iterator<Item = Rc<Object>>> = match *iterable {
+        Object::Range(range) => Box::new(range.iter().map(|i| Rc::new(Object::Integer(i)))),
+        _ => Box::new(iteration_items(&iterable)?.into_iter()),
+    };
+
+    for item in items {
+        let mut iteration_env = Environment::new_enclosed_environment(env);
+        iteration_env.set(variable.to_string(), item);
+
+        let evaluted_body = eval_block_statement(body, &Rc::new(RefCell::new(iteration_env)))?;
+        match *evaluted_body {
+            Object::ReturnValue(_) => return Ok(evaluted_body),
+            Object::Break => break,
+            _ => {}
+        }
+    }
+
+    Ok(Rc::new(Object::Null))
+}
+
+// `finally` runs however the `try` is left; only an error or a jump out of the `finally` block
+// itself replaces the outcome of the `try` and `catch` blocks.
+fn eval_try_expression(
+    body: &[Statement],
+    catch: Option<&(String, BlockStatement)>,
+    finally: Option<&[Statement]>,
+    env: &Env,
+) -> EvaluatorResult {
+    let mut result = eval_scoped_block(body, env);
+
+    if let (Err(err), Some((identifier, handler))) = (&result, catch) {
+        let mut catch_env = Environment::new_enclosed_environment(env);
+        catch_env.set(identifier.clone(), Rc::new(Object::Error(Rc::new(err.clone()))));
+        result = eval_block_statement(handler, &Rc::new(RefCell::new(catch_env)));
+    }
+
+    if let Some(finally) = finally {
+        let val = eval_scoped_block(finally, env)?;
+
+        if let Object::ReturnValue(_) | Object::Break | Object::Continue = *val {
+            return Ok(val);
+        }
+    }
+
+    result
+}
+
+pub(crate) fn iteration_items(iterable: &Object) -> Result<Vec<Rc<Object>>, EvaluatorError> {
+    match iterable {
+        Object::Array(elements) => Ok(elements.clone()),
+        Object::String(string) => Ok(string
+            .chars()
+            .map(|ch| Rc::new(Object::String(ch.to_string())))
+            .collect()),
+        Object::Hash(hash) => Ok(hash.keys().map(|key| Rc::new(key.to_object())).collect()),
+        object => Err(EvaluatorError::new(format!("Cannot iterate over {}", object))),
+    }
+}
+
+// A function body, or a branch of an `if` whose value the function returns. A `return`ed call,
+// or a call that is the last expression, is handed back to `apply_function` as a `TailCall`
+// instead of being run on top of the caller's native frames.
+fn eval_tail_block(statements: &[Statement], is_last: bool, env: &Env) -> EvaluatorResult {
+    let mut result = Rc::new(Object::Null);
+
+    for (i, statement) in statements.iter().enumerate() {
+        let val = match &statement.kind {
+            StatementKind::Return(expression) => {
+                Rc::new(Object::ReturnValue(eval_tail_expression(expression, true, env)?))
+            }
+            StatementKind::Expr(expression) => {
+                eval_tail_expression(expression, is_last && i == statements.len() - 1, env)?
+            }
+            _ => eval_statement(statement, &Rc::clone(env))?,
+        };
+
+        match *val {
+            Object::ReturnValue(_) | Object::Break | Object::Continue => return Ok(val),
             _ => result = val,
         }
     }
@@ -294,6 +946,38 @@ fn eval_block_statement(statements: &[Statement], env: &Env) -> EvaluatorResult
     Ok(result)
 }
 
+fn eval_tail_expression(expression: &Expression, is_tail: bool, env: &Env) -> EvaluatorResult {
+    match &expression.kind {
+        ExpressionKind::FunctionCall(function, args) if is_tail => {
+            let function = eval_expression(function, &Rc::clone(env))?;
+            let args = eval_expressions(args, env)?;
+            Ok(Rc::new(Object::TailCall(function, args, expression.span)))
+        }
+        ExpressionKind::If(condition, consequence, alternative) => {
+            let condition = eval_expression(condition, &Rc::clone(env))?;
+            let branch = if is_truthy(&condition) {
+                Some(consequence)
+            } else {
+                alternative.as_ref()
+            };
+
+            match branch {
+                Some(branch) => {
+                    let block_env = Environment::new_enclosed_environment(env);
+                    eval_tail_block(branch, is_tail, &Rc::new(RefCell::new(block_env)))
+                }
+                None => Ok(Rc::new(Object::Null)),
+            }
+        }
+        _ => eval_expression(expression, env),
+    }
+}
+
+fn eval_scoped_block(statements: &[Statement], env: &Env) -> EvaluatorResult {
+    let block_env = Environment::new_enclosed_environment(env);
+    eval_block_statement(statements, &Rc::new(RefCell::new(block_env)))
+}
+
 fn eval_identifier(identifier: &str, env: &Env) -> EvaluatorResult {
     let val = env.borrow().get(identifier);
 
@@ -301,39 +985,96 @@ fn eval_identifier(identifier: &str, env: &Env) -> EvaluatorResult {
         Some(val) => Ok(val.clone()),
         None => match Builtin::lookup(identifier) {
             Some(object) => Ok(Rc::new(object)),
-            None => Err(EvaluatorError::new(format!("Identifier not found: {}", identifier))),
+            None => Err(error_undeclared(
+                format!("Identifier not found: {}", identifier),
+                identifier,
+                env,
+            )),
         },
     }
 }
 
-fn apply_function(function: &Rc<Object>, args: &[Rc<Object>]) -> EvaluatorResult {
-    match &**function {
-        Object::Function(params, body, env) => {
-            let mut extended_env = Environment::new_enclosed_environment(&Rc::clone(env));
+fn error_undeclared(message: String, identifier: &str, env: &Env) -> EvaluatorError {
+    let names = env.borrow().names();
+    let candidates = names
+        .iter()
+        .map(String::as_str)
+        .chain(Builtin::ALL.iter().map(|builtin| builtin.name()))
+        .collect::<Vec<&str>>();
+
+    EvaluatorError::new(message).with_help(did_you_mean(identifier, candidates))
+}
+
+// Calls in tail position come back as a `TailCall` and run here, in place of the function that
+// made them, so tail recursion runs in constant native stack. The calls they replace still count
+// towards the call depth and show up in stack traces, with consecutive repeats stored once.
+fn apply_function(function: &Rc<Object>, args: &[Rc<Object>], call_site: Span) -> EvaluatorResult {
+    let mut replaced = TailCalls::default();
+    let mut call = (Rc::clone(function), args.to_vec(), call_site);
 
-            if params.len() != args.len() {
-                return Err(EvaluatorError::new(format!(
-                    "Expected {} arguments but got {}",
-                    params.len(),
-                    args.len()
-                )));
+    let result = loop {
+        let (function, args, call_site) = call;
+        let result = match &*function {
+            Object::Function(params, body, env, name) => {
+                call_function(params, body, env, name.as_deref(), &args, call_site)
             }
+            Object::Builtin(builtin) => builtin.apply(&args).map_err(|err| err.with_span(call_site)),
+            _ => Err(EvaluatorError::new(format!("Not a function: {}", function)).with_span(call_site)),
+        };
 
-            params.iter().enumerate().for_each(|(i, param)| {
-                extended_env.set(param.clone(), args[i].clone());
-            });
+        let next = match result.as_deref() {
+            Ok(Object::TailCall(function, args, call_site)) => (Rc::clone(function), args.clone(), *call_site),
+            _ => break result,
+        };
 
-            let evaluted_body = eval_block_statement(&body, &Rc::new(RefCell::new(extended_env)))?;
-            unwrap_return_value(evaluted_body)
-        }
-        Object::Builtin(builtin) => builtin.apply(args),
-        _ => Err(EvaluatorError::new(format!("Not a function: {}", function))),
+        let name = match &*function {
+            Object::Function(_, _, _, name) => name.as_deref(),
+            _ => None,
+        };
+        replaced.record(name, Some(call_site));
+        call = next;
+    };
+
+    result.map_err(|err| replaced.trace(err))
+}
+
+fn call_function(
+    params: &[String],
+    body: &[Statement],
+    env: &Env,
+    name: Option<&str>,
+    args: &[Rc<Object>],
+    call_site: Span,
+) -> EvaluatorResult {
+    let mut extended_env = Environment::new_enclosed_environment(&Rc::clone(env));
+
+    if params.len() != args.len() {
+        return Err(
+            EvaluatorError::new(format!("Expected {} arguments but got {}", params.len(), args.len()))
+                .with_span(call_site),
+        );
     }
+
+    params.iter().enumerate().for_each(|(i, param)| {
+        extended_env.set(param.clone(), args[i].clone());
+    });
+
+    let depth = CALL_DEPTH.with(Cell::get);
+    check_call_depth(depth)
+        .and_then(|_| check_stack_size(depth))
+        .map_err(|err| err.with_span(call_site))?;
+
+    CALL_DEPTH.with(|call_depth| call_depth.set(depth + 1));
+    let evaluted_body = with_stack(|| eval_tail_block(body, true, &Rc::new(RefCell::new(extended_env))));
+    CALL_DEPTH.with(|call_depth| call_depth.set(depth));
+
+    let evaluted_body = evaluted_body.map_err(|err| err.with_frame(StackFrame::new(name, Some(call_site))))?;
+    unwrap_return_value(evaluted_body)
 }
 
 fn unwrap_return_value(obj: Rc<Object>) -> EvaluatorResult {
     if let Object::ReturnValue(val) = &*obj {
-        Ok(Rc::clone(&val))
+        Ok(Rc::clone(val))
     } else {
         Ok(obj)
     }
@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod gc_test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        compiler::Compiler,
+        evaluator::{environment::Env, eval},
+        gc::{collect, live_objects, tracked_roots, LiveObjects, MIN_THRESHOLD},
+        parser::parser_test::parse,
+        vm::Vm,
+    };
+
+    fn eval_input(input: &str, env: &Env) -> String {
+        eval(parse(input).expect("Parser Error"), env)
+            .expect("Evaluator Error")
+            .to_string()
+    }
+
+    fn run_input(input: &str) -> String {
+        let bytecode = Compiler::default()
+            .compile(parse(input).expect("Parser Error"))
+            .expect("Compiler Error");
+        Vm::default().run(bytecode).expect("VM Error").to_string()
+    }
+
+    #[test]
+    fn test_recursive_function_cycles() {
+        let before = live_objects();
+
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        let input = "let countdown = fn(n) { if (n == 0) { 0 } else { countdown(n - 1) } }; countdown(10)";
+        assert_eq!(eval_input(input, &env), "0");
+        drop(env);
+
+        assert_ne!(live_objects(), before);
+        assert!(collect() > 0);
+        assert_eq!(live_objects(), before);
+    }
+
+    #[test]
+    fn test_nested_closure_cycles() {
+        let before = live_objects();
+
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        let input = "
+            let make = fn(x) {
+                let loop = fn(n) { if (n == 0) { x } else { loop(n - 1) } };
+                let escape = [fn() { loop }, {\"self\": loop}];
+                loop
+            };
+            let kept = make(7);
+            make(1)(3);
+            make(2)(3);
+            kept(5)";
+        assert_eq!(eval_input(input, &env), "7");
+
+        // `kept` is still reachable from the environment, and so is everything it closes over.
+        collect();
+        assert_eq!(eval_input("kept(2)", &env), "7");
+
+        drop(env);
+        collect();
+        assert_eq!(live_objects(), before);
+    }
+
+    #[test]
+    fn test_thrown_value_cycles() {
+        let before = live_objects();
+
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        let input = "let caught = try { throw fn() { caught } } catch (e) { e }; caught[\"value\"]()[\"message\"]";
+        assert_eq!(eval_input(input, &env), "fn() {...}");
+        drop(env);
+
+        collect();
+        assert_eq!(live_objects(), before);
+    }
+
+    #[test]
+    fn test_vm_closure_cycles() {
+        let before = live_objects();
+
+        let input = "
+            let make = fn(x) {
+                let loop = fn(n) { if (n == 0) { x } else { loop(n - 1) } };
+                loop
+            };
+            make(1)(3) + make(2)(3)";
+        assert_eq!(run_input(input), "3");
+
+        assert_ne!(live_objects(), before);
+        assert!(collect() > 0);
+        assert_eq!(live_objects(), before);
+    }
+
+    #[test]
+    fn test_tracked_environments() {
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        let before = tracked_roots();
+
+        let input = "let fns = [fn() { 1 }, fn() { 2 }, fn() { 3 }]; len(fns)";
+        assert_eq!(eval_input(input, &env), "3");
+        assert_eq!(tracked_roots() - before, 1);
+
+        // Every iteration has its own scope, which is gone once the loop is done.
+        let input = "let i = 0; while (i < 500) { let f = fn() { i }; i = i + 1 }; i";
+        assert_eq!(eval_input(input, &env), "500");
+        collect();
+        assert_eq!(tracked_roots() - before, 1);
+    }
+
+    #[test]
+    fn test_automatic_collection() {
+        let before = live_objects();
+
+        let input = "
+            let make = fn() { let again = fn() { again }; 0 };
+            for (let i = 0; i < 5000; i = i + 1) { make() };
+            0";
+        let env: Env = Rc::new(RefCell::new(Default::default()));
+        assert_eq!(eval_input(input, &env), "0");
+        assert_eq!(run_input(input), "0");
+
+        let LiveObjects { environments, closures } = live_objects();
+        assert!(environments - before.environments <= 2 * MIN_THRESHOLD);
+        assert!(closures - before.closures <= 2 * MIN_THRESHOLD);
+    }
+}
@@ -0,0 +1,302 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    evaluator::{
+        environment::{Env, Environment},
+        error::EvaluatorError,
+    },
+    object::Object,
+    vm::{Binding, Closure},
+};
+
+mod gc_test;
+
+// Collections run once this many captures were tracked, or twice the number that survived the
+// last collection, whichever is larger.
+const MIN_THRESHOLD: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LiveObjects {
+    pub environments: usize,
+    pub closures: usize,
+}
+
+// Only containers captured by a function can close a reference cycle, so those are the only
+// places a collection has to start from.
+enum Root {
+    Environment(Weak<RefCell<Environment>>),
+    Closure(Weak<Closure>),
+}
+
+#[derive(Default)]
+struct Registry {
+    roots: Vec<Root>,
+    threshold: usize,
+}
+
+thread_local! {
+    static LIVE: Cell<LiveObjects> = const {
+        Cell::new(LiveObjects {
+            environments: 0,
+            closures: 0,
+        })
+    };
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+pub fn live_objects() -> LiveObjects {
+    LIVE.with(|live| live.get())
+}
+
+pub(crate) fn environment_created() {
+    update_live(|live| live.environments += 1);
+}
+
+pub(crate) fn environment_dropped() {
+    update_live(|live| live.environments -= 1);
+}
+
+pub(crate) fn closure_created() {
+    update_live(|live| live.closures += 1);
+}
+
+pub(crate) fn closure_dropped() {
+    update_live(|live| live.closures -= 1);
+}
+
+// Values may still be dropped while the thread is tearing down its locals.
+fn update_live(change: impl FnOnce(&mut LiveObjects)) {
+    let _ = LIVE.try_with(|live| {
+        let mut counts = live.get();
+        change(&mut counts);
+        live.set(counts);
+    });
+}
+
+// Functions created in the same scope share its environment, which is only tracked once.
+pub(crate) fn track_environment(env: &Env) {
+    let tracked = env.try_borrow().is_ok_and(|env| env.mark_tracked());
+    if !tracked {
+        track(Root::Environment(Rc::downgrade(env)));
+    }
+}
+
+pub(crate) fn track_closure(closure: &Rc<Closure>) {
+    track(Root::Closure(Rc::downgrade(closure)));
+}
+
+#[cfg(test)]
+fn tracked_roots() -> usize {
+    REGISTRY.with(|registry| registry.borrow().roots.len())
+}
+
+fn track(root: Root) {
+    let due = REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        registry.roots.push(root);
+        registry.roots.len() >= registry.threshold.max(MIN_THRESHOLD)
+    });
+
+    if due {
+        collect();
+    }
+}
+
+// Frees reference cycles that nothing outside of them can reach anymore, returning how many
+// environments and bindings were cleared to break them.
+//
+// Every node's strong count is compared with the references it receives from the rest of the
+// traced graph; whatever is left over comes from the native stack, the VM or an embedder, which
+// keeps the node and everything it references alive. The rest can only be reached through cycles.
+pub fn collect() -> usize {
+    let roots = REGISTRY.with(|registry| std::mem::take(&mut registry.borrow_mut().roots));
+    let mut graph = Graph::default();
+
+    for root in &roots {
+        match root {
+            Root::Environment(env) => env.upgrade().map(|env| graph.add(Node::Environment(env))),
+            Root::Closure(closure) => closure.upgrade().map(|closure| graph.add(Node::Closure(closure))),
+        };
+    }
+
+    let freed = graph.collect();
+    drop(graph);
+
+    let mut seen = HashMap::new();
+    let survivors = roots
+        .into_iter()
+        .filter(|root| {
+            let (address, alive) = match root {
+                Root::Environment(env) => (env.as_ptr() as usize, env.strong_count() > 0),
+                Root::Closure(closure) => (closure.as_ptr() as usize, closure.strong_count() > 0),
+            };
+            alive && seen.insert(address, ()).is_none()
+        })
+        .collect::<Vec<Root>>();
+
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        registry.threshold = 2 * survivors.len();
+        registry.roots.extend(survivors);
+    });
+
+    freed
+}
+
+enum Node {
+    Environment(Env),
+    Binding(Binding),
+    Closure(Rc<Closure>),
+    Object(Rc<Object>),
+    Error(Rc<EvaluatorError>),
+}
+
+impl Node {
+    // Leaf values can't reference anything, so they are left out of the graph.
+    fn object(object: &Rc<Object>) -> Option<Node> {
+        match &**object {
+            Object::Array(_)
+            | Object::Hash(_)
+            | Object::ReturnValue(_)
+            | Object::TailCall(..)
+            | Object::Function(..)
+            | Object::Closure(_)
+            | Object::Error(_) => Some(Node::Object(Rc::clone(object))),
+            _ => None,
+        }
+    }
+
+    fn address(&self) -> usize {
+        match self {
+            Node::Environment(env) => Rc::as_ptr(env) as usize,
+            Node::Binding(binding) => Rc::as_ptr(binding) as usize,
+            Node::Closure(closure) => Rc::as_ptr(closure) as usize,
+            Node::Object(object) => Rc::as_ptr(object) as usize,
+            Node::Error(err) => Rc::as_ptr(err) as usize,
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        match self {
+            Node::Environment(env) => Rc::strong_count(env),
+            Node::Binding(binding) => Rc::strong_count(binding),
+            Node::Closure(closure) => Rc::strong_count(closure),
+            Node::Object(object) => Rc::strong_count(object),
+            Node::Error(err) => Rc::strong_count(err),
+        }
+    }
+
+    // `None` when the node is borrowed and its references can't be inspected.
+    fn children(&self) -> Option<Vec<Node>> {
+        let children = match self {
+            Node::Environment(env) => {
+                let env = env.try_borrow().ok()?;
+                let outer = env.outer().map(|outer| Node::Environment(Rc::clone(outer)));
+                env.values().filter_map(Node::object).chain(outer).collect()
+            }
+            Node::Binding(binding) => Node::object(&*binding.try_borrow().ok()?).into_iter().collect(),
+            Node::Closure(closure) => closure
+                .free
+                .iter()
+                .map(|binding| Node::Binding(Rc::clone(binding)))
+                .collect(),
+            Node::Object(object) => match &**object {
+                Object::Array(items) => items.iter().filter_map(Node::object).collect(),
+                Object::Hash(hash) => hash.values().filter_map(Node::object).collect(),
+                Object::ReturnValue(value) => Node::object(value).into_iter().collect(),
+                Object::TailCall(function, args, _) => Node::object(function)
+                    .into_iter()
+                    .chain(args.iter().filter_map(Node::object))
+                    .collect(),
+                Object::Function(_, _, env, _) => vec![Node::Environment(Rc::clone(env))],
+                Object::Closure(closure) => vec![Node::Closure(Rc::clone(closure))],
+                Object::Error(err) => vec![Node::Error(Rc::clone(err))],
+                _ => vec![],
+            },
+            Node::Error(err) => err.thrown_value().and_then(Node::object).into_iter().collect(),
+        };
+        Some(children)
+    }
+
+    // Drops the node's references so the cycle it is part of falls apart.
+    fn clear(&self) -> bool {
+        match self {
+            Node::Environment(env) => match env.try_borrow_mut() {
+                Ok(mut env) => {
+                    env.clear();
+                    true
+                }
+                Err(_) => false,
+            },
+            Node::Binding(binding) => match binding.try_borrow_mut() {
+                Ok(mut binding) => {
+                    *binding = Rc::new(Object::Null);
+                    true
+                }
+                Err(_) => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Graph {
+    nodes: Vec<Node>,
+    indexes: HashMap<usize, usize>,
+}
+
+impl Graph {
+    fn add(&mut self, node: Node) -> usize {
+        let address = node.address();
+        if let Some(&index) = self.indexes.get(&address) {
+            return index;
+        }
+
+        self.nodes.push(node);
+        self.indexes.insert(address, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    fn collect(&mut self) -> usize {
+        let mut edges: Vec<Option<Vec<usize>>> = vec![];
+        while edges.len() < self.nodes.len() {
+            let children = self.nodes[edges.len()].children();
+            edges.push(children.map(|children| children.into_iter().map(|child| self.add(child)).collect()));
+        }
+
+        // The graph holds one reference to every node itself.
+        let mut external = self
+            .nodes
+            .iter()
+            .map(|node| node.strong_count() - 1)
+            .collect::<Vec<usize>>();
+        for children in edges.iter().flatten() {
+            for &child in children {
+                external[child] = external[child].saturating_sub(1);
+            }
+        }
+
+        let mut reachable = vec![false; self.nodes.len()];
+        let mut pending = (0..self.nodes.len())
+            .filter(|&index| external[index] > 0 || edges[index].is_none())
+            .collect::<Vec<usize>>();
+        while let Some(index) = pending.pop() {
+            if reachable[index] {
+                continue;
+            }
+            reachable[index] = true;
+            pending.extend(edges[index].iter().flatten());
+        }
+
+        self.nodes
+            .iter()
+            .zip(reachable)
+            .filter(|(node, reachable)| !reachable && node.clear())
+            .count()
+    }
+}
@@ -1,12 +1,16 @@
 #[cfg(test)]
 mod lexer_test {
-    use crate::{lexer::Lexer, token::Token};
+    use crate::{
+        bigint::BigInt,
+        lexer::Lexer,
+        token::{Position, Span, TemplatePart, Token},
+    };
 
-    fn test_runner(input: &str, expected: &Vec<Token>) {
-        let mut lexer = Lexer::new(&input);
+    fn test_runner(input: &str, expected: &[Token]) {
+        let mut lexer = Lexer::new(input);
 
         for test in expected.iter() {
-            let token = lexer.next_token();
+            let token = lexer.next_token().token;
             assert_eq!(&token, test);
         }
     }
@@ -107,6 +111,53 @@ mod lexer_test {
         test_runner(test, &expected);
     }
 
+    #[test]
+    fn test_loop_control_tokens() {
+        let test = "while (true) { break; continue; }";
+
+        let expected = vec![
+            Token::WHILE,
+            Token::LPAREN,
+            Token::BOOLEAN(true),
+            Token::RPAREN,
+            Token::LBRACE,
+            Token::BREAK,
+            Token::SEMICOLON,
+            Token::CONTINUE,
+            Token::SEMICOLON,
+            Token::RBRACE,
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
+    #[test]
+    fn test_exception_tokens() {
+        let test = "try { throw e; } catch (e) { } finally { }";
+
+        let expected = vec![
+            Token::TRY,
+            Token::LBRACE,
+            Token::THROW,
+            Token::IDENT("e".to_string()),
+            Token::SEMICOLON,
+            Token::RBRACE,
+            Token::CATCH,
+            Token::LPAREN,
+            Token::IDENT("e".to_string()),
+            Token::RPAREN,
+            Token::LBRACE,
+            Token::RBRACE,
+            Token::FINALLY,
+            Token::LBRACE,
+            Token::RBRACE,
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
     #[test]
     fn test_operator_tokens() {
         let test = r#"
@@ -174,6 +225,98 @@ mod lexer_test {
         test_runner(test, &expected);
     }
 
+    #[test]
+    fn test_string_escape_tokens() {
+        let test = r#"
+                "line\nnext\ttab"
+                "say \"hi\" \\ done"
+                "\u{48}\u{e9}\u{1F600}\0"
+                "two
+lines"
+                "#;
+
+        let expected = vec![
+            Token::STRING("line\nnext\ttab".to_string()),
+            Token::STRING("say \"hi\" \\ done".to_string()),
+            Token::STRING("Hé😀\0".to_string()),
+            Token::STRING("two\nlines".to_string()),
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
+    #[test]
+    fn test_template_tokens() {
+        let test = r#""hi ${name}!" "${ {"a": "}"}["a"] }" "\${x}" "${1"#;
+
+        let position = |column| Position { line: 1, column };
+        let expected = vec![
+            Token::TEMPLATE(vec![
+                TemplatePart::Text("hi ".to_string()),
+                TemplatePart::Code("name".to_string(), position(7)),
+                TemplatePart::Text("!".to_string()),
+            ]),
+            Token::TEMPLATE(vec![TemplatePart::Code(
+                r#" {"a": "}"}["a"] "#.to_string(),
+                position(18),
+            )]),
+            Token::STRING("${x}".to_string()),
+            Token::ERROR("Unterminated interpolation, expected `}`".to_string()),
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
+    #[test]
+    fn test_raw_string_tokens() {
+        let test = r##"
+                r"C:\path\n"
+                r#"say "hi""#
+                r"multi
+line" rest
+                "##;
+
+        let expected = vec![
+            Token::STRING("C:\\path\\n".to_string()),
+            Token::STRING("say \"hi\"".to_string()),
+            Token::STRING("multi\nline".to_string()),
+            Token::IDENT("rest".to_string()),
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
+    #[test]
+    fn test_invalid_string_tokens() {
+        let tests = [
+            (r#""bad \q escape" 1"#, "Unknown escape sequence: \\q"),
+            (r#""\u{110000}" 1"#, "Invalid unicode escape: \\u{110000}"),
+            (r#""\u{41" 1"#, "Unterminated unicode escape, expected `}`"),
+        ];
+
+        for (input, message) in tests {
+            test_runner(input, &[Token::ERROR(message.to_string()), Token::INT(1), Token::EOF]);
+        }
+
+        test_runner(
+            "let s = \"never closed;",
+            &[
+                Token::LET,
+                Token::IDENT("s".to_string()),
+                Token::ASSIGN,
+                Token::ERROR("Unterminated string literal".to_string()),
+                Token::EOF,
+            ],
+        );
+        test_runner(
+            "r#\"open\"",
+            &[Token::ERROR("Unterminated raw string literal".to_string()), Token::EOF],
+        );
+    }
+
     #[test]
     fn test_single_line_comment_tokens() {
         let test = r#"
@@ -230,4 +373,212 @@ mod lexer_test {
 
         test_runner(test, &expected);
     }
+
+    #[test]
+    fn test_identifiers_with_digits() {
+        let test = "x1 _tmp2 snake_case9 π café 日本 1x a·b";
+
+        let expected = vec![
+            Token::IDENT("x1".to_string()),
+            Token::IDENT("_tmp2".to_string()),
+            Token::IDENT("snake_case9".to_string()),
+            Token::IDENT("π".to_string()),
+            Token::IDENT("café".to_string()),
+            Token::IDENT("日本".to_string()),
+            Token::INT(1),
+            Token::IDENT("x".to_string()),
+            Token::IDENT("a·b".to_string()),
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
+    #[test]
+    fn test_illegal_tokens() {
+        let test = "a # b & c | @ 🙂";
+
+        let expected = vec![
+            Token::IDENT("a".to_string()),
+            Token::ILLEGAL('#'),
+            Token::IDENT("b".to_string()),
+            Token::ILLEGAL('&'),
+            Token::IDENT("c".to_string()),
+            Token::ILLEGAL('|'),
+            Token::ILLEGAL('@'),
+            Token::ILLEGAL('🙂'),
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
+    #[test]
+    fn test_hash_tokens() {
+        let test = r#"{"foo": "bar", 1: true}"#;
+
+        let expected = vec![
+            Token::LBRACE,
+            Token::STRING("foo".to_string()),
+            Token::COLON,
+            Token::STRING("bar".to_string()),
+            Token::COMMA,
+            Token::INT(1),
+            Token::COLON,
+            Token::BOOLEAN(true),
+            Token::RBRACE,
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
+    #[test]
+    fn test_range_tokens() {
+        let test = "0..10 1..=n x.";
+
+        let expected = vec![
+            Token::INT(0),
+            Token::DOTDOT,
+            Token::INT(10),
+            Token::INT(1),
+            Token::DOTDOT_EQ,
+            Token::IDENT("n".to_string()),
+            Token::IDENT("x".to_string()),
+            Token::ILLEGAL('.'),
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
+    #[test]
+    fn test_assignment_operator_tokens() {
+        let test = "x += 1; x -= 1; x *= 2; x /= 2; x %= 3; x++; --x; a - -b";
+
+        let expected = vec![
+            Token::IDENT("x".to_string()),
+            Token::PLUS_ASSIGN,
+            Token::INT(1),
+            Token::SEMICOLON,
+            Token::IDENT("x".to_string()),
+            Token::MINUS_ASSIGN,
+            Token::INT(1),
+            Token::SEMICOLON,
+            Token::IDENT("x".to_string()),
+            Token::ASTERISK_ASSIGN,
+            Token::INT(2),
+            Token::SEMICOLON,
+            Token::IDENT("x".to_string()),
+            Token::SLASH_ASSIGN,
+            Token::INT(2),
+            Token::SEMICOLON,
+            Token::IDENT("x".to_string()),
+            Token::PERCENT_ASSIGN,
+            Token::INT(3),
+            Token::SEMICOLON,
+            Token::IDENT("x".to_string()),
+            Token::INCREMENT,
+            Token::SEMICOLON,
+            Token::DECREMENT,
+            Token::IDENT("x".to_string()),
+            Token::SEMICOLON,
+            Token::IDENT("a".to_string()),
+            Token::MINUS,
+            Token::MINUS,
+            Token::IDENT("b".to_string()),
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
+    #[test]
+    fn test_modulo_token() {
+        let test = "10 % 3;";
+
+        let expected = vec![
+            Token::INT(10),
+            Token::PERCENT,
+            Token::INT(3),
+            Token::SEMICOLON,
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
+    #[test]
+    fn test_float_tokens() {
+        let test = "2.75 0.5 1e-3 2E5 7.5e+2 10 1e x.5";
+
+        let expected = vec![
+            Token::FLOAT(2.75),
+            Token::FLOAT(0.5),
+            Token::FLOAT(0.001),
+            Token::FLOAT(200000.0),
+            Token::FLOAT(750.0),
+            Token::INT(10),
+            Token::INT(1),
+            Token::IDENT("e".to_string()),
+            Token::IDENT("x".to_string()),
+            Token::ILLEGAL('.'),
+            Token::INT(5),
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
+    #[test]
+    fn test_big_integer_literal() {
+        let test = "9223372036854775807 9223372036854775808;";
+
+        let expected = vec![
+            Token::INT(9223372036854775807),
+            Token::BIGINT(BigInt::parse("9223372036854775808").unwrap()),
+            Token::SEMICOLON,
+            Token::EOF,
+        ];
+
+        test_runner(test, &expected);
+    }
+
+    #[test]
+    fn test_token_spans() {
+        let test = "let x = 10;
+                 // comment
+                 x == \"ab\"";
+
+        let mut lexer = Lexer::new(test);
+
+        let expected = [
+            (Token::LET, (1, 1), (1, 4)),
+            (Token::IDENT("x".to_string()), (1, 5), (1, 6)),
+            (Token::ASSIGN, (1, 7), (1, 8)),
+            (Token::INT(10), (1, 9), (1, 11)),
+            (Token::SEMICOLON, (1, 11), (1, 12)),
+            (Token::IDENT("x".to_string()), (3, 18), (3, 19)),
+            (Token::EQ, (3, 20), (3, 22)),
+            (Token::STRING("ab".to_string()), (3, 23), (3, 27)),
+            (Token::EOF, (3, 27), (3, 28)),
+        ];
+
+        for (token, (start_line, start_column), (end_line, end_column)) in expected {
+            let spanned = lexer.next_token();
+            let span = Span::new(
+                Position {
+                    line: start_line,
+                    column: start_column,
+                },
+                Position {
+                    line: end_line,
+                    column: end_column,
+                },
+            );
+
+            assert_eq!(spanned.token, token);
+            assert_eq!(spanned.span, span, "span of {}", token);
+        }
+    }
 }
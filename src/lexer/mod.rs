@@ -1,4 +1,9 @@
-use crate::token::Token;
+use unicode_ident::{is_xid_continue, is_xid_start};
+
+use crate::{
+    bigint::BigInt,
+    token::{Position, Span, SpannedToken, TemplatePart, Token},
+};
 
 mod lexer_test;
 
@@ -8,12 +13,15 @@ pub struct Lexer {
     position: usize,
     read_position: usize,
     ch: char,
+    line: usize,
+    column: usize,
 }
 
 impl Lexer {
     pub fn new(input: &str) -> Self {
         let mut lexer = Self {
             input: input.chars().collect::<Vec<char>>(),
+            line: 1,
             ..Default::default()
         };
 
@@ -21,10 +29,34 @@ impl Lexer {
         lexer
     }
 
-    pub fn next_token(&mut self) -> Token {
-        let token: Token;
+    // Lexes source embedded in a larger input, such as an interpolated expression, so that spans
+    // point into the original text.
+    pub fn new_at(input: &str, position: Position) -> Self {
+        let mut lexer = Self {
+            input: input.chars().collect::<Vec<char>>(),
+            line: position.line,
+            column: position.column - 1,
+            ..Default::default()
+        };
+
+        lexer.read_char();
+        lexer
+    }
+
+    pub fn next_token(&mut self) -> SpannedToken {
+        self.skip_whitespace_and_comments();
+
+        let start = self.current_position();
+        let token = self.read_token();
 
-        self.skip_whitespace();
+        SpannedToken {
+            token,
+            span: Span::new(start, self.current_position()),
+        }
+    }
+
+    fn read_token(&mut self) -> Token {
+        let token: Token;
 
         match self.ch {
             '=' => {
@@ -49,28 +81,45 @@ impl Lexer {
             '[' => token = Token::LBRACKET,
             ']' => token = Token::RBRACKET,
             ',' => token = Token::COMMA,
-            '+' => token = Token::PLUS,
-            '-' => token = Token::MINUS,
-            '/' => {
-                if self.peek_char() == '/' {
-                    self.skip_single_line_comment();
-                    return self.next_token();
-                } else if self.peek_char() == '*' {
-                    self.skip_multi_line_comment();
-                    return self.next_token();
-                } else {
-                    token = Token::SLASH
+            ':' => token = Token::COLON,
+            '+' => match self.peek_char() {
+                '+' => {
+                    self.read_char();
+                    token = Token::INCREMENT
                 }
-            }
-            '*' => token = Token::ASTERISK,
+                _ => token = self.operator_or_assign(Token::PLUS, Token::PLUS_ASSIGN),
+            },
+            '-' => match self.peek_char() {
+                '-' => {
+                    self.read_char();
+                    token = Token::DECREMENT
+                }
+                _ => token = self.operator_or_assign(Token::MINUS, Token::MINUS_ASSIGN),
+            },
+            '/' => token = self.operator_or_assign(Token::SLASH, Token::SLASH_ASSIGN),
+            '%' => token = self.operator_or_assign(Token::PERCENT, Token::PERCENT_ASSIGN),
+            '*' => token = self.operator_or_assign(Token::ASTERISK, Token::ASTERISK_ASSIGN),
             '<' => token = Token::LT,
             '>' => token = Token::GT,
+            '.' => {
+                if self.peek_char() == '.' {
+                    self.read_char();
+                    if self.peek_char() == '=' {
+                        self.read_char();
+                        token = Token::DOTDOT_EQ
+                    } else {
+                        token = Token::DOTDOT
+                    }
+                } else {
+                    token = Token::ILLEGAL(self.ch)
+                }
+            }
             '&' => {
                 if self.peek_char() == '&' {
                     self.read_char();
                     token = Token::AND
                 } else {
-                    token = Token::ILLEGAL
+                    token = Token::ILLEGAL(self.ch)
                 }
             }
             '|' => {
@@ -78,18 +127,16 @@ impl Lexer {
                     self.read_char();
                     token = Token::OR
                 } else {
-                    token = Token::ILLEGAL
+                    token = Token::ILLEGAL(self.ch)
                 }
             }
             '{' => token = Token::LBRACE,
             '}' => token = Token::RBRACE,
             '\0' => token = Token::EOF,
-            '"' => {
-                let string_literal = self.read_string();
-                token = Token::STRING(string_literal);
-            }
+            '"' => token = self.read_string(),
+            'r' if matches!(self.peek_char(), '"' | '#') => token = self.read_raw_string(),
             ch => {
-                if ch.is_alphabetic() || ch == '_' {
+                if is_identifier_start(ch) {
                     let idenfifier = self.read_identifier();
                     return match idenfifier.as_str() {
                         "let" => Token::LET,
@@ -98,24 +145,47 @@ impl Lexer {
                         "false" => Token::BOOLEAN(false),
                         "if" => Token::IF,
                         "while" => Token::WHILE,
+                        "for" => Token::FOR,
+                        "in" => Token::IN,
                         "else" => Token::ELSE,
                         "return" => Token::RETURN,
+                        "break" => Token::BREAK,
+                        "continue" => Token::CONTINUE,
+                        "throw" => Token::THROW,
+                        "try" => Token::TRY,
+                        "catch" => Token::CATCH,
+                        "finally" => Token::FINALLY,
                         _ => Token::IDENT(idenfifier),
                     };
-                } else if ch.is_digit(10) {
-                    let num = self.read_number();
-                    return Token::INT(num);
+                } else if ch.is_ascii_digit() {
+                    return self.read_number();
                 } else {
-                    token = Token::ILLEGAL;
+                    token = Token::ILLEGAL(ch);
                 }
             }
         }
 
         self.read_char();
-        return token;
+        token
+    }
+
+    fn operator_or_assign(&mut self, operator: Token, assign: Token) -> Token {
+        if self.peek_char() == '=' {
+            self.read_char();
+            assign
+        } else {
+            operator
+        }
     }
 
     fn read_char(&mut self) {
+        if self.ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
         if self.read_position >= self.input.len() {
             self.ch = '\0';
         } else {
@@ -125,10 +195,17 @@ impl Lexer {
         self.read_position += 1;
     }
 
+    fn current_position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
     fn read_identifier(&mut self) -> String {
         let start_index = self.position;
 
-        while self.ch.is_alphabetic() || self.ch == '_' {
+        while is_xid_continue(self.ch) {
             self.read_char()
         }
 
@@ -137,20 +214,55 @@ impl Lexer {
         self.input[start_index..end_index].iter().collect()
     }
 
-    fn read_number(&mut self) -> i32 {
+    fn read_number(&mut self) -> Token {
         let start_index = self.position;
+        let mut is_float = false;
 
-        while self.ch.is_digit(10) {
+        self.read_digits();
+
+        if self.ch == '.' && self.peek_char().is_ascii_digit() {
+            is_float = true;
             self.read_char();
+            self.read_digits();
+        }
+
+        if self.is_exponent_start() {
+            is_float = true;
+            self.read_char();
+            if self.ch == '+' || self.ch == '-' {
+                self.read_char();
+            }
+            self.read_digits();
         }
 
         let end_index = self.position;
+        let literal = self.input[start_index..end_index].iter().collect::<String>();
+
+        if is_float {
+            Token::FLOAT(literal.parse::<f64>().expect("Error in parsing float literal"))
+        } else {
+            match literal.parse::<i64>() {
+                Ok(integer) => Token::INT(integer),
+                Err(_) => Token::BIGINT(BigInt::parse(&literal).expect("Error in parsing integer literal")),
+            }
+        }
+    }
 
-        self.input[start_index..end_index]
-            .iter()
-            .collect::<String>()
-            .parse::<i32>()
-            .expect("Error in parsing sequence of numbers")
+    fn read_digits(&mut self) {
+        while self.ch.is_ascii_digit() {
+            self.read_char();
+        }
+    }
+
+    fn is_exponent_start(&self) -> bool {
+        if self.ch != 'e' && self.ch != 'E' {
+            return false;
+        }
+
+        match self.peek_char() {
+            '+' | '-' => self.peek_nth_char(1).is_ascii_digit(),
+            ch => ch.is_ascii_digit(),
+        }
     }
 
     fn skip_whitespace(&mut self) {
@@ -159,12 +271,22 @@ impl Lexer {
         }
     }
 
+    fn skip_whitespace_and_comments(&mut self) {
+        loop {
+            self.skip_whitespace();
+
+            match (self.ch, self.peek_char()) {
+                ('/', '/') => self.skip_single_line_comment(),
+                ('/', '*') => self.skip_multi_line_comment(),
+                _ => break,
+            }
+        }
+    }
+
     fn skip_single_line_comment(&mut self) {
         while self.ch != '\n' && self.ch != '\0' {
             self.read_char();
         }
-
-        self.skip_whitespace()
     }
 
     fn skip_multi_line_comment(&mut self) {
@@ -180,28 +302,173 @@ impl Lexer {
             }
             self.read_char();
         }
-
-        self.skip_whitespace()
     }
 
     fn peek_char(&self) -> char {
-        if self.read_position >= self.input.len() {
-            '\0'
-        } else {
-            self.input[self.read_position]
+        self.peek_nth_char(0)
+    }
+
+    fn peek_nth_char(&self, n: usize) -> char {
+        match self.input.get(self.read_position + n) {
+            Some(ch) => *ch,
+            None => '\0',
         }
     }
 
-    fn read_string(&mut self) -> String {
+    // An invalid escape is reported once the whole literal has been read, so lexing resumes after it.
+    fn read_string(&mut self) -> Token {
         let mut string = String::new();
+        let mut parts = vec![];
+        let mut error = None;
 
         self.read_char();
 
         while self.ch != '"' {
-            string.push(self.ch);
+            match self.ch {
+                '\0' => return Token::ERROR("Unterminated string literal".to_string()),
+                '$' if self.peek_char() == '{' => {
+                    if !string.is_empty() {
+                        parts.push(TemplatePart::Text(std::mem::take(&mut string)));
+                    }
+                    self.read_char();
+                    match self.read_interpolation() {
+                        Some(part) => parts.push(part),
+                        None => return Token::ERROR("Unterminated interpolation, expected `}`".to_string()),
+                    }
+                }
+                '\\' => {
+                    self.read_char();
+                    match self.read_escape() {
+                        Ok(ch) => string.push(ch),
+                        Err(message) => {
+                            error.get_or_insert(message);
+                        }
+                    }
+                }
+                ch => string.push(ch),
+            }
             self.read_char();
         }
 
-        string
+        if let Some(message) = error {
+            return Token::ERROR(message);
+        }
+
+        if parts.is_empty() {
+            Token::STRING(string)
+        } else {
+            if !string.is_empty() {
+                parts.push(TemplatePart::Text(string));
+            }
+            Token::TEMPLATE(parts)
+        }
+    }
+
+    // Reads the source of an embedded `${...}` expression up to its matching brace, skipping over
+    // braces and quotes inside nested string literals.
+    fn read_interpolation(&mut self) -> Option<TemplatePart> {
+        self.read_char();
+
+        let position = self.current_position();
+        let mut code = String::new();
+        let mut depth = 0;
+
+        loop {
+            match self.ch {
+                '\0' => return None,
+                '}' if depth == 0 => return Some(TemplatePart::Code(code, position)),
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                '"' => {
+                    code.push(self.ch);
+                    self.read_char();
+
+                    while self.ch != '"' && self.ch != '\0' {
+                        if self.ch == '\\' {
+                            code.push(self.ch);
+                            self.read_char();
+                        }
+                        code.push(self.ch);
+                        self.read_char();
+                    }
+                }
+                _ => {}
+            }
+
+            code.push(self.ch);
+            self.read_char();
+        }
+    }
+
+    fn read_escape(&mut self) -> Result<char, String> {
+        match self.ch {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            '$' => Ok('$'),
+            'u' if self.peek_char() == '{' => self.read_unicode_escape(),
+            ch => Err(format!("Unknown escape sequence: \\{}", ch)),
+        }
     }
+
+    fn read_unicode_escape(&mut self) -> Result<char, String> {
+        let mut digits = String::new();
+
+        self.read_char();
+        while self.peek_char().is_ascii_hexdigit() {
+            self.read_char();
+            digits.push(self.ch);
+        }
+
+        if self.peek_char() != '}' {
+            return Err("Unterminated unicode escape, expected `}`".to_string());
+        }
+        self.read_char();
+
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .filter(|_| digits.len() <= 6)
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("Invalid unicode escape: \\u{{{}}}", digits))
+    }
+
+    // Raw strings are written `r"..."`, or `r#"..."#` with any number of `#` to allow quotes
+    // inside. They span lines and have no escapes.
+    fn read_raw_string(&mut self) -> Token {
+        let mut hashes = 0;
+
+        self.read_char();
+        while self.ch == '#' {
+            hashes += 1;
+            self.read_char();
+        }
+
+        if self.ch != '"' {
+            return Token::ERROR("Expected `\"` to start a raw string literal".to_string());
+        }
+
+        let mut string = String::new();
+
+        loop {
+            self.read_char();
+            match self.ch {
+                '\0' => return Token::ERROR("Unterminated raw string literal".to_string()),
+                '"' if (0..hashes).all(|n| self.peek_nth_char(n) == '#') => break,
+                ch => string.push(ch),
+            }
+        }
+
+        for _ in 0..hashes {
+            self.read_char();
+        }
+
+        Token::STRING(string)
+    }
+}
+
+fn is_identifier_start(ch: char) -> bool {
+    ch == '_' || is_xid_start(ch)
 }
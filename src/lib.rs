@@ -1,8 +1,15 @@
+#![allow(clippy::module_inception)]
+
 pub mod ast;
+pub mod bigint;
+pub mod compiler;
+pub mod diagnostics;
 pub mod evaluator;
+pub mod gc;
 pub mod lexer;
 pub mod object;
 pub mod parser;
 pub mod repl;
 pub mod token;
+pub mod vm;
 pub mod wasm;
@@ -1,27 +1,43 @@
 use core::fmt;
-use std::rc::Rc;
+use std::{collections::BTreeMap, rc::Rc};
 
 use crate::{
     ast::BlockStatement,
-    evaluator::{builtins::Builtin, environment::Env},
+    bigint::BigInt,
+    compiler::CompiledFunction,
+    evaluator::{builtins::Builtin, environment::Env, error::EvaluatorError},
+    token::Span,
+    vm::Closure,
 };
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Object {
-    Integer(i32),
+    Integer(i64),
+    BigInt(BigInt),
+    Float(f64),
     Boolean(bool),
     String(String),
     Array(Vec<Rc<Object>>),
+    Hash(BTreeMap<HashKey, Rc<Object>>),
+    Range(Range),
     Null,
     ReturnValue(Rc<Object>),
-    Function(Vec<String>, BlockStatement, Env),
+    Break,
+    Continue,
+    TailCall(Rc<Object>, Vec<Rc<Object>>, Span),
+    Function(Vec<String>, BlockStatement, Env, Option<String>),
     Builtin(Builtin),
+    CompiledFunction(Rc<CompiledFunction>),
+    Closure(Rc<Closure>),
+    Error(Rc<EvaluatorError>),
 }
 
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Object::Integer(i) => write!(f, "{}", i),
+            Object::BigInt(i) => write!(f, "{}", i),
+            Object::Float(float) => write!(f, "{:?}", float),
             Object::Boolean(b) => write!(f, "{}", b),
             Object::String(s) => write!(f, "{}", s),
             Object::Array(arr) => write!(
@@ -32,12 +48,103 @@ impl fmt::Display for Object {
                     .collect::<Vec<String>>()
                     .join(", ")
             ),
+            Object::Hash(hash) => write!(
+                f,
+                "{{{}}}",
+                hash.iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Object::Range(range) => write!(f, "{}", range),
             Object::Null => write!(f, "null"),
             Object::ReturnValue(obj) => write!(f, "{}", obj),
-            Object::Function(params, _body, _env) => {
+            Object::Break => write!(f, "break"),
+            Object::Continue => write!(f, "continue"),
+            Object::TailCall(function, _args, _call_site) => write!(f, "{}", function),
+            Object::Function(params, _body, _env, _name) => {
                 write!(f, "fn({}) {{...}}", params.join(","))
             }
             Object::Builtin(builtin) => write!(f, "Builtin Function: {}", builtin),
+            Object::CompiledFunction(function) => write!(f, "{}", function),
+            Object::Closure(closure) => write!(f, "{}", closure),
+            Object::Error(err) => write!(f, "Error: {}", err),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    pub start: i64,
+    pub end: i64,
+    pub inclusive: bool,
+}
+
+impl Range {
+    pub fn len(&self) -> i128 {
+        let end = self.end as i128 + i128::from(self.inclusive);
+        (end - self.start as i128).max(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: i128) -> Option<i64> {
+        if index >= 0 && index < self.len() {
+            Some((self.start as i128 + index) as i64)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        value >= self.start && (value < self.end || (self.inclusive && value == self.end))
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = i64> {
+        (0..self.len()).map(move |index| (self.start as i128 + index) as i64)
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operator = if self.inclusive { "..=" } else { ".." };
+        write!(f, "{}{}{}", self.start, operator, self.end)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HashKey {
+    Integer(i64),
+    BigInt(BigInt),
+    Boolean(bool),
+    String(String),
+}
+
+impl HashKey {
+    pub fn from_object(object: &Object) -> Option<Self> {
+        match object {
+            Object::Integer(i) => Some(HashKey::Integer(*i)),
+            Object::BigInt(i) => Some(HashKey::BigInt(i.clone())),
+            Object::Boolean(b) => Some(HashKey::Boolean(*b)),
+            Object::String(s) => Some(HashKey::String(s.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn to_object(&self) -> Object {
+        match self {
+            HashKey::Integer(i) => Object::Integer(*i),
+            HashKey::BigInt(i) => Object::BigInt(i.clone()),
+            HashKey::Boolean(b) => Object::Boolean(*b),
+            HashKey::String(s) => Object::String(s.clone()),
         }
     }
 }
+
+impl fmt::Display for HashKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_object())
+    }
+}
@@ -1,18 +1,27 @@
 use std::fmt;
 
+use crate::token::Span;
+
 pub type ParserErrors = Vec<ParserError>;
 
 #[derive(Debug, Clone)]
-pub struct ParserError(String);
+pub struct ParserError {
+    message: String,
+    span: Span,
+}
 
 impl fmt::Display for ParserError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.message)
     }
 }
 
 impl ParserError {
-    pub fn new(msg: String) -> Self {
-        ParserError(msg)
+    pub fn new(message: String, span: Span) -> Self {
+        ParserError { message, span }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
     }
 }
@@ -1,7 +1,7 @@
 use crate::{
-    ast::{BlockStatement, Expression, Literal, Statement},
+    ast::{BlockStatement, Expression, ExpressionKind, Literal, Statement, StatementKind},
     lexer::Lexer,
-    token::Token,
+    token::{Span, SpannedToken, TemplatePart, Token},
 };
 
 use self::{
@@ -9,7 +9,7 @@ use self::{
     precedence::{token_to_precedence, Precedence},
 };
 
-mod error;
+pub mod error;
 pub mod parser_test;
 mod precedence;
 
@@ -17,60 +17,98 @@ mod precedence;
 pub struct Parser {
     lexer: Lexer,
     current_token: Token,
+    current_span: Span,
     peek_token: Token,
+    peek_span: Span,
     errors: Vec<ParserError>,
+    loop_depth: usize,
+    brace_depth: usize,
 }
 
 impl Parser {
     pub fn new(mut lexer: Lexer) -> Self {
-        let current_token = lexer.next_token();
-        let peek_token = lexer.next_token();
+        let current = lexer.next_token();
+        let peek = lexer.next_token();
         let errors = vec![];
 
-        Parser {
+        let mut parser = Parser {
             lexer,
-            current_token,
-            peek_token,
+            current_token: current.token,
+            current_span: current.span,
+            peek_token: peek.token,
+            peek_span: peek.span,
             errors,
-        }
+            loop_depth: 0,
+            brace_depth: 0,
+        };
+        parser.track_brace_depth();
+        parser
     }
 
     fn next_token(&mut self) {
-        self.current_token = self.peek_token.clone();
-        self.peek_token = self.lexer.next_token();
+        let SpannedToken { token, span } = self.lexer.next_token();
+
+        self.current_token = std::mem::replace(&mut self.peek_token, token);
+        self.current_span = std::mem::replace(&mut self.peek_span, span);
+        self.track_brace_depth();
+    }
+
+    // Counts the braces opened up to and including the current token, so that recovery can find
+    // the end of the block a failed statement belongs to.
+    fn track_brace_depth(&mut self) {
+        match self.current_token {
+            Token::LBRACE => self.brace_depth += 1,
+            Token::RBRACE => self.brace_depth = self.brace_depth.saturating_sub(1),
+            _ => {}
+        }
     }
 
     fn parse_program(&mut self) -> Result<Vec<Statement>, ParserErrors> {
+        let program = self.parse_statements();
+
+        if self.errors.is_empty() {
+            Ok(program)
+        } else {
+            Err(self.errors.clone())
+        }
+    }
+
+    // Parses every statement it can, collecting the errors of those it can't.
+    fn parse_statements(&mut self) -> Vec<Statement> {
         let mut program = Vec::new();
 
         while !self.current_token_is(&Token::EOF) {
             match self.parse_statement() {
                 Ok(statement) => program.push(statement),
-                Err(e) => self.errors.push(e),
+                Err(e) => {
+                    self.errors.push(e);
+                    self.synchronize(0);
+                }
             }
             self.next_token();
         }
 
-        if self.errors.is_empty() {
-            Ok(program)
-        } else {
-            Err(self.errors.clone())
-        }
+        program
     }
 
     fn parse_statement(&mut self) -> Result<Statement, ParserError> {
         match self.current_token {
             Token::LET => self.parse_let_statement(),
             Token::RETURN => self.parse_return_statement(),
+            Token::THROW => self.parse_throw_statement(),
+            Token::BREAK => Ok(self.parse_loop_control_statement(StatementKind::Break)),
+            Token::CONTINUE => Ok(self.parse_loop_control_statement(StatementKind::Continue)),
             _ => self.parse_expression_statement(),
         }
     }
 
     fn parse_let_statement(&mut self) -> Result<Statement, ParserError> {
+        let start = self.current_span;
+
         let identifier = match &self.peek_token {
             Token::IDENT(ref id) => id.clone(),
             token => {
-                return Err(self.error_no_identifier(token));
+                return Err(self.error_no_identifier(token, self.peek_span));
             }
         };
         self.next_token();
@@ -78,16 +116,40 @@ impl Parser {
         self.expect_peek(&Token::ASSIGN)?;
         self.next_token();
 
-        let expression = self.parse_expression(Precedence::LOWEST)?;
+        let mut expression = self.parse_expression(Precedence::LOWEST)?;
+
+        if let ExpressionKind::Function(_, _, name) = &mut expression.kind {
+            *name = Some(identifier.clone());
+        }
 
         if self.peek_token_is(&Token::SEMICOLON) {
             self.next_token();
         }
 
-        Ok(Statement::Let(identifier, expression))
+        Ok(Statement::new(
+            StatementKind::Let(identifier, expression),
+            start.to(self.current_span),
+        ))
     }
 
     fn parse_return_statement(&mut self) -> Result<Statement, ParserError> {
+        let start = self.current_span;
+        self.next_token();
+
+        let expression = self.parse_expression(Precedence::LOWEST)?;
+
+        if self.peek_token_is(&Token::SEMICOLON) {
+            self.next_token();
+        }
+
+        Ok(Statement::new(
+            StatementKind::Return(expression),
+            start.to(self.current_span),
+        ))
+    }
+
+    fn parse_throw_statement(&mut self) -> Result<Statement, ParserError> {
+        let start = self.current_span;
         self.next_token();
 
         let expression = self.parse_expression(Precedence::LOWEST)?;
@@ -96,36 +158,81 @@ impl Parser {
             self.next_token();
         }
 
-        Ok(Statement::Return(expression))
+        Ok(Statement::new(
+            StatementKind::Throw(expression),
+            start.to(self.current_span),
+        ))
+    }
+
+    // Misplaced `break`/`continue` is still well-formed syntax, so the error is recorded
+    // without aborting the enclosing statement.
+    fn parse_loop_control_statement(&mut self, kind: StatementKind) -> Statement {
+        let start = self.current_span;
+
+        if self.loop_depth == 0 {
+            self.errors.push(ParserError::new(
+                format!("`{}` outside of a loop", self.current_token),
+                start,
+            ));
+        }
+
+        if self.peek_token_is(&Token::SEMICOLON) {
+            self.next_token();
+        }
+
+        Statement::new(kind, start.to(self.current_span))
     }
 
     fn parse_expression_statement(&mut self) -> Result<Statement, ParserError> {
+        let start = self.current_span;
         let expression = self.parse_expression(Precedence::LOWEST)?;
 
         if self.peek_token_is(&Token::SEMICOLON) {
             self.next_token();
         }
 
-        Ok(Statement::Expr(expression))
+        Ok(Statement::new(
+            StatementKind::Expr(expression),
+            start.to(self.current_span),
+        ))
     }
 
     fn parse_expression(&mut self, precedence: Precedence) -> Result<Expression, ParserError> {
+        let span = self.current_span;
         let mut left_expression = match self.current_token {
-            Token::IDENT(ref id) => Ok(Expression::Ident(id.clone())),
-            Token::INT(value) => Ok(Expression::Lit(Literal::Integer(value))),
-            Token::BOOLEAN(boolean) => Ok(Expression::Lit(Literal::Boolean(boolean))),
-            Token::STRING(ref string) => Ok(Expression::Lit(Literal::String(string.clone()))),
+            Token::IDENT(ref id) => Ok(Expression::new(ExpressionKind::Ident(id.clone()), span)),
+            Token::INT(value) => Ok(Expression::new(ExpressionKind::Lit(Literal::Integer(value)), span)),
+            Token::BIGINT(ref value) => Ok(Expression::new(
+                ExpressionKind::Lit(Literal::BigInt(value.clone())),
+                span,
+            )),
+            Token::FLOAT(value) => Ok(Expression::new(ExpressionKind::Lit(Literal::Float(value)), span)),
+            Token::BOOLEAN(boolean) => Ok(Expression::new(ExpressionKind::Lit(Literal::Boolean(boolean)), span)),
+            Token::STRING(ref string) => Ok(Expression::new(
+                ExpressionKind::Lit(Literal::String(string.clone())),
+                span,
+            )),
+            Token::TEMPLATE(ref parts) => {
+                let parts = parts.clone();
+                self.parse_interpolation(parts)
+            }
             Token::BANG | Token::MINUS => self.parse_prefix_expression(),
+            Token::INCREMENT | Token::DECREMENT => self.parse_prefix_update_expression(),
             Token::LPAREN => self.parse_group_expression(),
             Token::IF => self.parse_if_expression(),
             Token::WHILE => self.parse_while_expression(),
+            Token::TRY => self.parse_try_expression(),
+            Token::FOR => self.parse_for_expression(),
             Token::FUNCTION => self.parse_fn_expressions(),
             Token::LBRACKET => self.parse_array_literal(),
+            Token::LBRACE => self.parse_hash_literal(),
+            Token::ERROR(ref message) => return Err(ParserError::new(message.clone(), span)),
+            Token::ILLEGAL(ch) => return Err(ParserError::new(format!("Unexpected character `{}`", ch), span)),
             _ => {
-                return Err(ParserError::new(format!(
-                    "no prefix parse function for {:?}",
-                    self.current_token
-                )))
+                return Err(ParserError::new(
+                    format!("no prefix parse function for {:?}", self.current_token),
+                    span,
+                ))
             }
         };
 
@@ -140,31 +247,33 @@ impl Parser {
                 | Token::ASTERISK
                 | Token::AND
                 | Token::OR
-                | Token::SLASH => {
+                | Token::SLASH
+                | Token::PERCENT
+                | Token::DOTDOT
+                | Token::DOTDOT_EQ => {
                     self.next_token();
-                    let expression = left_expression.unwrap();
-                    left_expression = self.parse_infix_expression(expression)
+                    left_expression = self.parse_infix_expression(left_expression?)
                 }
                 Token::LPAREN => {
                     self.next_token();
-                    let expression = left_expression.unwrap();
-                    left_expression = self.parse_call_expression(expression)
+                    left_expression = self.parse_call_expression(left_expression?)
                 }
                 Token::LBRACKET => {
                     self.next_token();
-                    let expression = left_expression.unwrap();
-                    left_expression = self.parse_index_expression(expression)
+                    left_expression = self.parse_index_expression(left_expression?)
                 }
-                Token::ASSIGN => {
-                    let iden = match &self.current_token {
-                        Token::IDENT(ref id) => id.clone(),
-                        token => {
-                            return Err(self.error_no_identifier(token));
-                        }
-                    };
+                Token::ASSIGN
+                | Token::PLUS_ASSIGN
+                | Token::MINUS_ASSIGN
+                | Token::ASTERISK_ASSIGN
+                | Token::SLASH_ASSIGN
+                | Token::PERCENT_ASSIGN => {
                     self.next_token();
-
-                    left_expression = self.parse_assignment_expression(iden)
+                    left_expression = self.parse_assignment_expression(left_expression?)
+                }
+                Token::INCREMENT | Token::DECREMENT => {
+                    self.next_token();
+                    left_expression = self.parse_postfix_update_expression(left_expression?)
                 }
                 _ => return left_expression,
             }
@@ -173,13 +282,52 @@ impl Parser {
         left_expression
     }
 
+    fn parse_interpolation(&mut self, parts: Vec<TemplatePart>) -> Result<Expression, ParserError> {
+        let span = self.current_span;
+        let mut expressions = Vec::with_capacity(parts.len());
+
+        for part in parts {
+            match part {
+                TemplatePart::Text(text) => {
+                    expressions.push(Expression::new(ExpressionKind::Lit(Literal::String(text)), span))
+                }
+                TemplatePart::Code(code, position) => {
+                    let mut parser = Parser::new(Lexer::new_at(&code, position));
+
+                    if parser.current_token_is(&Token::EOF) {
+                        return Err(ParserError::new(
+                            "Expected an expression inside `${}`".to_string(),
+                            parser.current_span,
+                        ));
+                    }
+
+                    let mut expression = parser.parse_expression(Precedence::LOWEST);
+                    if expression.is_ok() && !parser.peek_token_is(&Token::EOF) {
+                        expression = Err(ParserError::new(
+                            format!("Expected `}}` to end interpolation, got {:?}", parser.peek_token),
+                            parser.peek_span,
+                        ));
+                    }
+
+                    // The embedded lexer starts at the expression's position, so these already
+                    // point inside the template.
+                    self.errors.append(&mut parser.errors);
+                    expressions.push(expression?);
+                }
+            }
+        }
+
+        Ok(Expression::new(ExpressionKind::Interpolation(expressions), span))
+    }
+
     fn parse_prefix_expression(&mut self) -> Result<Expression, ParserError> {
+        let start = self.current_span;
         let operator = self.current_token.clone();
         self.next_token();
 
         let right_expression = self.parse_expression(Precedence::PREFIX)?;
 
-        Ok(Expression::Prefix(operator, Box::new(right_expression)))
+        Ok(self.expression_from(ExpressionKind::Prefix(operator, Box::new(right_expression)), start))
     }
 
     fn parse_infix_expression(&mut self, left_expression: Expression) -> Result<Expression, ParserError> {
@@ -190,10 +338,10 @@ impl Parser {
 
         let right_expression = self.parse_expression(precedence)?;
 
-        Ok(Expression::Infix(
-            Box::new(left_expression),
-            infix_operator,
-            Box::new(right_expression),
+        let start = left_expression.span;
+        Ok(self.expression_from(
+            ExpressionKind::Infix(Box::new(left_expression), infix_operator, Box::new(right_expression)),
+            start,
         ))
     }
 
@@ -205,6 +353,7 @@ impl Parser {
     }
 
     fn parse_if_expression(&mut self) -> Result<Expression, ParserError> {
+        let start = self.current_span;
         self.expect_peek(&Token::LPAREN)?;
         self.next_token();
 
@@ -223,10 +372,11 @@ impl Parser {
             None
         };
 
-        Ok(Expression::If(Box::new(condition), consequence, alternative))
+        Ok(self.expression_from(ExpressionKind::If(Box::new(condition), consequence, alternative), start))
     }
 
     fn parse_while_expression(&mut self) -> Result<Expression, ParserError> {
+        let start = self.current_span;
         self.expect_peek(&Token::LPAREN)?;
         self.next_token();
 
@@ -235,19 +385,137 @@ impl Parser {
         self.expect_peek(&Token::RPAREN)?;
         self.expect_peek(&Token::LBRACE)?;
 
-        let body = self.parse_block_statement()?;
+        let body = self.parse_loop_body()?;
+
+        Ok(self.expression_from(ExpressionKind::While(Box::new(condition), body), start))
+    }
+
+    fn parse_try_expression(&mut self) -> Result<Expression, ParserError> {
+        let start = self.current_span;
+        self.expect_peek(&Token::LBRACE)?;
+
+        let block = self.parse_block_statement()?;
+
+        let catch = if self.peek_token_is(&Token::CATCH) {
+            self.next_token();
+            self.expect_peek(&Token::LPAREN)?;
+            self.next_token();
+
+            let identifier = match &self.current_token {
+                Token::IDENT(identifier) => identifier.clone(),
+                token => return Err(self.error_no_identifier(token, self.current_span)),
+            };
+
+            self.expect_peek(&Token::RPAREN)?;
+            self.expect_peek(&Token::LBRACE)?;
+
+            Some((identifier, self.parse_block_statement()?))
+        } else {
+            None
+        };
+
+        let finally = if self.peek_token_is(&Token::FINALLY) {
+            self.next_token();
+            self.expect_peek(&Token::LBRACE)?;
+
+            Some(self.parse_block_statement()?)
+        } else {
+            None
+        };
 
-        Ok(Expression::While(Box::new(condition), body))
+        if catch.is_none() && finally.is_none() {
+            return Err(ParserError::new(
+                format!(
+                    "Expected `catch` or `finally` after `try` block, got {:?}",
+                    self.peek_token
+                ),
+                self.peek_span,
+            ));
+        }
+
+        Ok(self.expression_from(ExpressionKind::Try(block, catch, finally), start))
+    }
+
+    fn parse_for_expression(&mut self) -> Result<Expression, ParserError> {
+        let start = self.current_span;
+        self.expect_peek(&Token::LPAREN)?;
+        self.next_token();
+
+        if let (Token::IDENT(variable), Token::IN) = (&self.current_token, &self.peek_token) {
+            let variable = variable.clone();
+            self.next_token();
+            self.next_token();
+
+            let iterable = self.parse_expression(Precedence::LOWEST)?;
+
+            self.expect_peek(&Token::RPAREN)?;
+            self.expect_peek(&Token::LBRACE)?;
+
+            let body = self.parse_loop_body()?;
+
+            return Ok(self.expression_from(ExpressionKind::ForIn(variable, Box::new(iterable), body), start));
+        }
+
+        let init = if self.current_token_is(&Token::SEMICOLON) {
+            None
+        } else {
+            let init = self.parse_statement()?;
+            if !self.current_token_is(&Token::SEMICOLON) {
+                self.expect_peek(&Token::SEMICOLON)?;
+            }
+            Some(Box::new(init))
+        };
+        self.next_token();
+
+        let condition = if self.current_token_is(&Token::SEMICOLON) {
+            None
+        } else {
+            let condition = self.parse_expression(Precedence::LOWEST)?;
+            self.expect_peek(&Token::SEMICOLON)?;
+            Some(Box::new(condition))
+        };
+        self.next_token();
+
+        let update = if self.current_token_is(&Token::RPAREN) {
+            None
+        } else {
+            let update = self.parse_expression(Precedence::LOWEST)?;
+            self.expect_peek(&Token::RPAREN)?;
+            Some(Box::new(update))
+        };
+
+        self.expect_peek(&Token::LBRACE)?;
+
+        let body = self.parse_loop_body()?;
+
+        Ok(self.expression_from(ExpressionKind::For(init, condition, update, body), start))
+    }
+
+    fn parse_loop_body(&mut self) -> Result<BlockStatement, ParserError> {
+        self.loop_depth += 1;
+        let body = self.parse_block_statement();
+        self.loop_depth -= 1;
+        body
     }
 
     fn parse_block_statement(&mut self) -> Result<BlockStatement, ParserError> {
+        let depth = self.brace_depth;
         self.next_token();
 
         let mut statements = Vec::new();
 
         while !self.current_token_is(&Token::RBRACE) && !self.current_token_is(&Token::EOF) {
-            if let Ok(statement) = self.parse_statement() {
-                statements.push(statement);
+            match self.parse_statement() {
+                Ok(statement) => statements.push(statement),
+                Err(e) => {
+                    self.errors.push(e);
+                    self.synchronize(depth);
+
+                    // The failed statement ran into the block's closing brace.
+                    if self.brace_depth < depth {
+                        break;
+                    }
+                }
             }
             self.next_token();
         }
@@ -255,16 +523,35 @@ impl Parser {
         Ok(statements)
     }
 
+    // Skips the rest of a statement that failed to parse, stopping on its last token so that the
+    // caller's `next_token` lands on the next statement at the same brace depth: after a `;`,
+    // before a statement keyword or before the `}` closing the enclosing block.
+    fn synchronize(&mut self, depth: usize) {
+        while self.brace_depth >= depth && !self.peek_token_is(&Token::EOF) {
+            if self.brace_depth == depth {
+                let at_block_end = depth > 0 && self.peek_token_is(&Token::RBRACE);
+
+                if self.current_token_is(&Token::SEMICOLON) || at_block_end || starts_statement(&self.peek_token) {
+                    return;
+                }
+            }
+            self.next_token();
+        }
+    }
+
     fn parse_fn_expressions(&mut self) -> Result<Expression, ParserError> {
+        let start = self.current_span;
         self.expect_peek(&Token::LPAREN)?;
 
         let parameters = self.parse_fn_parameters()?;
 
         self.expect_peek(&Token::LBRACE)?;
 
-        let body = self.parse_block_statement()?;
+        let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let body = self.parse_block_statement();
+        self.loop_depth = loop_depth;
 
-        Ok(Expression::Function(parameters, body))
+        Ok(self.expression_from(ExpressionKind::Function(parameters, body?, None), start))
     }
 
     fn parse_fn_parameters(&mut self) -> Result<Vec<String>, ParserError> {
@@ -279,7 +566,7 @@ impl Parser {
 
         match &self.current_token {
             Token::IDENT(ref id) => parameters.push(id.clone()),
-            token => return Err(self.error_no_identifier(token)),
+            token => return Err(self.error_no_identifier(token, self.current_span)),
         }
 
         while self.peek_token_is(&Token::COMMA) {
@@ -288,7 +575,7 @@ impl Parser {
 
             match &self.current_token {
                 Token::IDENT(ref id) => parameters.push(id.clone()),
-                token => return Err(self.error_no_identifier(token)),
+                token => return Err(self.error_no_identifier(token, self.current_span)),
             }
         }
 
@@ -300,21 +587,73 @@ impl Parser {
     fn parse_call_expression(&mut self, function: Expression) -> Result<Expression, ParserError> {
         let arguments = self.parse_expression_list(&Token::RPAREN)?;
 
-        Ok(Expression::FunctionCall(Box::new(function), arguments))
+        let start = function.span;
+        Ok(self.expression_from(ExpressionKind::FunctionCall(Box::new(function), arguments), start))
     }
 
     fn parse_array_literal(&mut self) -> Result<Expression, ParserError> {
+        let start = self.current_span;
         let arr = self.parse_expression_list(&Token::RBRACKET)?;
 
-        Ok(Expression::Lit(Literal::Array(arr)))
+        Ok(self.expression_from(ExpressionKind::Lit(Literal::Array(arr)), start))
+    }
+
+    fn parse_hash_literal(&mut self) -> Result<Expression, ParserError> {
+        let start = self.current_span;
+        let mut pairs = Vec::new();
+
+        while !self.peek_token_is(&Token::RBRACE) {
+            self.next_token();
+            let key = self.parse_expression(Precedence::LOWEST)?;
+
+            self.expect_peek(&Token::COLON)?;
+            self.next_token();
+            let value = self.parse_expression(Precedence::LOWEST)?;
+
+            pairs.push((key, value));
+
+            if !self.peek_token_is(&Token::RBRACE) {
+                self.expect_peek(&Token::COMMA)?;
+            }
+        }
+
+        self.expect_peek(&Token::RBRACE)?;
+
+        Ok(self.expression_from(ExpressionKind::Lit(Literal::Hash(pairs)), start))
     }
 
-    fn parse_assignment_expression(&mut self, left: String) -> Result<Expression, ParserError> {
+    fn parse_assignment_expression(&mut self, target: Expression) -> Result<Expression, ParserError> {
+        let operator = self.current_token.clone();
+        check_assignable(&target)?;
+
         self.next_token();
 
         let right = self.parse_expression(Precedence::LOWEST)?;
 
-        Ok(Expression::Assign(left, Token::ASSIGN, Box::new(right)))
+        let start = target.span;
+        Ok(self.expression_from(
+            ExpressionKind::Assign(Box::new(target), operator, Box::new(right)),
+            start,
+        ))
+    }
+
+    fn parse_prefix_update_expression(&mut self) -> Result<Expression, ParserError> {
+        let start = self.current_span;
+        let operator = self.current_token.clone();
+        self.next_token();
+
+        let target = self.parse_expression(Precedence::PREFIX)?;
+        check_assignable(&target)?;
+
+        Ok(self.expression_from(ExpressionKind::PrefixUpdate(operator, Box::new(target)), start))
+    }
+
+    fn parse_postfix_update_expression(&mut self, target: Expression) -> Result<Expression, ParserError> {
+        let operator = self.current_token.clone();
+        check_assignable(&target)?;
+
+        let start = target.span;
+        Ok(self.expression_from(ExpressionKind::PostfixUpdate(Box::new(target), operator), start))
     }
 
     fn parse_expression_list(&mut self, end: &Token) -> Result<Vec<Expression>, ParserError> {
@@ -344,15 +683,41 @@ impl Parser {
     fn parse_index_expression(&mut self, left: Expression) -> Result<Expression, ParserError> {
         self.next_token();
 
-        let index = self.parse_expression(Precedence::LOWEST)?;
+        let slice_start = if self.current_token_is(&Token::COLON) {
+            None
+        } else {
+            let index = self.parse_expression(Precedence::LOWEST)?;
+
+            if !self.peek_token_is(&Token::COLON) {
+                self.expect_peek(&Token::RBRACKET)?;
+
+                let start = left.span;
+                return Ok(self.expression_from(ExpressionKind::Index(Box::new(left), Box::new(index)), start));
+            }
+
+            self.next_token();
+            Some(Box::new(index))
+        };
+
+        let slice_end = if self.peek_token_is(&Token::RBRACKET) {
+            None
+        } else {
+            self.next_token();
+            Some(Box::new(self.parse_expression(Precedence::LOWEST)?))
+        };
 
         self.expect_peek(&Token::RBRACKET)?;
 
-        Ok(Expression::Index(Box::new(left), Box::new(index)))
+        let start = left.span;
+        Ok(self.expression_from(ExpressionKind::Slice(Box::new(left), slice_start, slice_end), start))
     }
 
-    fn error_no_identifier(&self, token: &Token) -> ParserError {
-        ParserError::new(format!("Expected an identifier but got {:?}", token.clone()))
+    fn expression_from(&self, kind: ExpressionKind, start: Span) -> Expression {
+        Expression::new(kind, start.to(self.current_span))
+    }
+
+    fn error_no_identifier(&self, token: &Token, span: Span) -> ParserError {
+        ParserError::new(format!("Expected an identifier but got {:?}", token.clone()), span)
     }
 
     fn current_token_is(&self, token: &Token) -> bool {
@@ -368,10 +733,36 @@ impl Parser {
             self.next_token();
             Ok(())
         } else {
-            Err(ParserError::new(format!(
-                "Expected {:?}, got {:?}",
-                token, self.peek_token
-            )))
+            Err(ParserError::new(
+                format!("Expected {:?}, got {:?}", token, self.peek_token),
+                self.peek_span,
+            ))
         }
     }
 }
+
+fn starts_statement(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::LET | Token::RETURN | Token::BREAK | Token::CONTINUE | Token::THROW | Token::WHILE | Token::FOR
+    )
+}
+
+fn check_assignable(target: &Expression) -> Result<(), ParserError> {
+    if is_assignable(target) {
+        Ok(())
+    } else {
+        Err(ParserError::new(
+            format!("Invalid assignment target: {}", target),
+            target.span,
+        ))
+    }
+}
+
+fn is_assignable(target: &Expression) -> bool {
+    match &target.kind {
+        ExpressionKind::Ident(_) => true,
+        ExpressionKind::Index(left, _) => is_assignable(left),
+        _ => false,
+    }
+}
@@ -27,6 +27,10 @@ pub mod parser_test {
         }
     }
     use super::*;
+    use crate::{
+        ast::{ExpressionKind, StatementKind},
+        token::Position,
+    };
 
     #[test]
     fn test_let_statements() {
@@ -35,6 +39,12 @@ pub mod parser_test {
             ("let y = true;", "let y = true;"),
             ("let foobar = y;", "let foobar = y;"),
             ("let x = 5; x = x + 5; x;", "let x = 5;x = (x + 5)x"),
+            ("xs[0] = 1", "(xs[0]) = 1"),
+            ("x += 1; y -= 2 * 3", "x += 1y -= (2 * 3)"),
+            ("xs[i] *= y /= 2", "(xs[i]) *= y /= 2"),
+            ("a %= b + c", "a %= (b + c)"),
+            ("\"a ${x + 1} b ${f(\"}\")}\"", "\"a ${(x + 1)} b ${f(\"}\")}\""),
+            ("m[\"a\"][i + 1] = y = 2", "((m[\"a\"])[(i + 1)]) = y = 2"),
         ];
 
         test_runner(&tests);
@@ -65,6 +75,23 @@ pub mod parser_test {
         test_runner(&tests);
     }
 
+    #[test]
+    fn test_float_literal_expression() {
+        let tests = [("3.5;", "3.5"), ("2.0 * 1e3", "(2.0 * 1000.0)"), ("-0.25", "(-0.25)")];
+
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_big_integer_literal_expression() {
+        let tests = [
+            ("9223372036854775808", "9223372036854775808"),
+            ("-123456789012345678901234567890", "(-123456789012345678901234567890)"),
+        ];
+
+        test_runner(&tests);
+    }
+
     #[test]
     fn test_parse_prefix_expression() {
         let tests = [
@@ -92,6 +119,10 @@ pub mod parser_test {
             ("5 != 5;", "(5 != 5)"),
             ("true && false", "(true && false)"),
             ("false || true", "(false || true)"),
+            ("1 == 1 && 2 != 3", "((1 == 1) && (2 != 3))"),
+            ("a < b || c > d && e", "((a < b) || ((c > d) && e))"),
+            ("a && b || c && d", "((a && b) || (c && d))"),
+            ("x != null && x[0] > 1", "((x != null) && ((x[0]) > 1))"),
         ];
 
         test_runner(&tests);
@@ -107,6 +138,13 @@ pub mod parser_test {
             ("a * b * c", "((a * b) * c)"),
             ("a * b / c", "((a * b) / c)"),
             ("a + b / c", "(a + (b / c))"),
+            ("a + b % c * d", "(a + ((b % c) * d))"),
+            ("i++ + ++j", "((i++) + (++j))"),
+            ("-x--", "(-(x--))"),
+            ("a[0]++ * 2", "(((a[0])++) * 2)"),
+            ("--xs[i]", "(--(xs[i]))"),
+            ("0..n + 1", "(0 .. (n + 1))"),
+            ("a < 0..=b", "(a < (0 ..= b))"),
             ("a + b * c + d / e - f", "(((a + (b * c)) + (d / e)) - f)"),
             ("3 + 4; -5 * 5", "(3 + 4)((-5) * 5)"),
             ("5 > 4 == 3 < 4", "((5 > 4) == (3 < 4))"),
@@ -133,6 +171,9 @@ pub mod parser_test {
                 "add(a * b[2], b[1], 2 * [1, 2][1])",
                 "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))",
             ),
+            ("a[1:n + 1]", "(a[1:(n + 1)])"),
+            ("a[:2] + b[-2:]", "((a[:2]) + (b[(-2):]))"),
+            ("s[:]", "(s[:])"),
         ];
 
         test_runner(&tests);
@@ -192,4 +233,254 @@ pub mod parser_test {
         let test_case = [("myArray[1 + 1];", "(myArray[(1 + 1)])")];
         test_runner(&test_case);
     }
+
+    #[test]
+    fn test_for_expressions() {
+        let tests = [
+            (
+                "for (let i = 0; i < 3; i = i + 1) { x }",
+                "for (let i = 0; (i < 3); i = (i + 1)) { x }",
+            ),
+            ("for (;;) { break; }", "for (; ; ) { break; }"),
+            ("for (i = 0; ; ) { }", "for (i = 0; ; ) {  }"),
+            ("for (x in xs) { continue; }", "for (x in xs) { continue; }"),
+            ("for (x in [1, 2]) { x }", "for (x in [1, 2]) { x }"),
+        ];
+
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_loop_control_statements() {
+        let tests = [
+            ("while (x) { break; }", "while x { break; }"),
+            ("while (x) { if (y) { continue } }", "while x { if y { continue; } }"),
+        ];
+
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_exception_statements() {
+        let tests = [
+            ("throw \"boom\";", "throw \"boom\";"),
+            ("throw x + 1", "throw (x + 1);"),
+            ("try { f() } catch (e) { e }", "try { f() } catch (e) { e }"),
+            ("try { a; b } finally { c }", "try { ab } finally { c }"),
+            (
+                "let r = try { 1 } catch (err) { 2 } finally { 3 };",
+                "let r = try { 1 } catch (err) { 2 } finally { 3 };",
+            ),
+        ];
+
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_error_spans() {
+        let test_case = [
+            ("let = 5;", "1:5", "Expected an identifier but got ASSIGN"),
+            ("add(1, 2", "1:9", "Expected RPAREN, got EOF"),
+            ("let x = 1;\nlet y = );", "2:9", "no prefix parse function for RPAREN"),
+            ("break;", "1:1", "`break` outside of a loop"),
+            (
+                "for (let i = 0 i < 3; ) { }",
+                "1:16",
+                "Expected SEMICOLON, got IDENT(\"i\")",
+            ),
+            ("if (true) {\n  continue;\n}", "2:3", "`continue` outside of a loop"),
+            ("while (true) { fn() { break; } }", "1:23", "`break` outside of a loop"),
+            ("a + b = 1", "1:1", "Invalid assignment target: (a + b)"),
+            ("let x = 1;\nlet y = x + @;", "2:13", "Unexpected character `@`"),
+            ("let total = 1 # 2;", "1:15", "Unexpected character `#`"),
+            ("let s = \"abc;\nlen(s)", "1:9", "Unterminated string literal"),
+            ("puts(\"\\x\")", "1:6", "Unknown escape sequence: \\x"),
+            ("let s = \"sum: ${1 +}\"", "1:20", "no prefix parse function for EOF"),
+            ("\"${}\"", "1:4", "Expected an expression inside `${}`"),
+            (
+                "\"${a b}\"",
+                "1:6",
+                "Expected `}` to end interpolation, got IDENT(\"b\")",
+            ),
+            ("let x = 1;\n\"${x + \"\\q\"}\"", "2:8", "Unknown escape sequence: \\q"),
+            ("1++", "1:1", "Invalid assignment target: 1"),
+            ("--f()", "1:3", "Invalid assignment target: f()"),
+            ("(a + b) -= 1", "1:2", "Invalid assignment target: (a + b)"),
+            ("f()[0] = 1", "1:1", "Invalid assignment target: (f()[0])"),
+            (
+                "try { 1 }\nlet x = 2;",
+                "2:1",
+                "Expected `catch` or `finally` after `try` block, got LET",
+            ),
+            ("try { 1 } catch { 2 }", "1:17", "Expected LPAREN, got LBRACE"),
+            (
+                "try { 1 } catch (1) { 2 }",
+                "1:18",
+                "Expected an identifier but got INT(1)",
+            ),
+        ];
+
+        for (input, span, message) in test_case {
+            let errors = parse(input).expect_err("Expected a parsing error");
+
+            assert_eq!(errors[0].span().to_string(), span, "span of: {}", input);
+            assert_eq!(errors[0].to_string(), message, "message of: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_error_recovery() {
+        let test_case: [(&str, &[(&str, &str)]); 7] = [
+            (
+                "let x = ;\nlet add = fn(a, b) { a + };\nlet y = (1 + 2;\nlet z = x + y;",
+                &[
+                    ("1:9", "no prefix parse function for SEMICOLON"),
+                    ("2:26", "no prefix parse function for RBRACE"),
+                    ("3:15", "Expected RPAREN, got SEMICOLON"),
+                ],
+            ),
+            (
+                "let f = fn() {\n  let = 1;\n  let ok = 2;\n  return ok +;\n};\nf()",
+                &[
+                    ("2:7", "Expected an identifier but got ASSIGN"),
+                    ("4:14", "no prefix parse function for SEMICOLON"),
+                ],
+            ),
+            (
+                "if (x { let h = {1: }; h } else { 1 }\nlet a = 1;\nlet = 2",
+                &[
+                    ("1:7", "Expected RPAREN, got LBRACE"),
+                    ("3:5", "Expected an identifier but got ASSIGN"),
+                ],
+            ),
+            ("let a = 1 let b = @ let c = 3", &[("1:19", "Unexpected character `@`")]),
+            (
+                "while (true) { let x = [1, 2; break; }\n}\nlet ok = 1;",
+                &[
+                    ("1:29", "Expected RBRACKET, got SEMICOLON"),
+                    ("2:1", "no prefix parse function for RBRACE"),
+                ],
+            ),
+            (
+                "let s = \"${fn() { let = 1; 2 }() +}\";",
+                &[
+                    ("1:23", "Expected an identifier but got ASSIGN"),
+                    ("1:35", "no prefix parse function for EOF"),
+                ],
+            ),
+            (
+                "let t = 1;\nlet u = \"a ${fn() { let = 1; t }(t 2)} b\";",
+                &[
+                    ("2:25", "Expected an identifier but got ASSIGN"),
+                    ("2:36", "Expected RPAREN, got INT(2)"),
+                ],
+            ),
+        ];
+
+        for (input, expected) in test_case {
+            let errors = parse(input).expect_err("Expected parsing errors");
+            let errors = errors
+                .iter()
+                .map(|error| (error.span().to_string(), error.to_string()))
+                .collect::<Vec<(String, String)>>();
+            let expected = expected
+                .iter()
+                .map(|(span, message)| (span.to_string(), message.to_string()))
+                .collect::<Vec<(String, String)>>();
+
+            assert_eq!(errors, expected, "errors of: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_recovery_from_failed_operands() {
+        let test_case = [
+            (
+                "(x +) * 2; let y = 1;",
+                vec![("1:5", "no prefix parse function for RPAREN")],
+                vec!["let y = 1;"],
+            ),
+            (
+                "let a = [1, (2 *)][0]; let b = f(-)(1) + 2; let c = 3;",
+                vec![
+                    ("1:17", "no prefix parse function for RPAREN"),
+                    ("1:35", "no prefix parse function for RPAREN"),
+                ],
+                vec!["let c = 3;"],
+            ),
+        ];
+
+        for (input, expected_errors, expected_statements) in test_case {
+            let mut parser = Parser::new(Lexer::new(input));
+            let statements = parser
+                .parse_statements()
+                .iter()
+                .map(|statement| statement.to_string())
+                .collect::<Vec<String>>();
+            let errors = parser
+                .errors
+                .iter()
+                .map(|error| (error.span().to_string(), error.to_string()))
+                .collect::<Vec<(String, String)>>();
+            let expected_errors = expected_errors
+                .iter()
+                .map(|(span, message)| (span.to_string(), message.to_string()))
+                .collect::<Vec<(String, String)>>();
+
+            assert_eq!(errors, expected_errors, "errors of: {}", input);
+            assert_eq!(statements, expected_statements, "statements of: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_node_spans() {
+        let program = match parse("let x = 1;\nx = -x + add(2, 3);") {
+            Ok(Node::Program(program)) => program,
+            result => panic!("Expected a program, got {:?}", result),
+        };
+
+        let spans = program
+            .iter()
+            .map(|statement| (statement.span.start, statement.span.end))
+            .collect::<Vec<_>>();
+        let expected = [(1, 1, 1, 11), (2, 1, 2, 20)]
+            .iter()
+            .map(|(start_line, start_column, end_line, end_column)| {
+                (
+                    Position {
+                        line: *start_line,
+                        column: *start_column,
+                    },
+                    Position {
+                        line: *end_line,
+                        column: *end_column,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(spans, expected);
+
+        match &program[1].kind {
+            StatementKind::Expr(expression) => match &expression.kind {
+                ExpressionKind::Assign(_, _, value) => {
+                    assert_eq!(value.span.start.column, 5);
+                    assert_eq!(value.span.end.column, 19);
+                }
+                kind => panic!("Expected an assignment, got {}", kind),
+            },
+            kind => panic!("Expected an expression statement, got {}", kind),
+        }
+    }
+
+    #[test]
+    fn test_hash_literal_expression() {
+        let test_case = [
+            ("{}", "{}"),
+            (r#"{"one": 1, "two": 2}"#, r#"{"one": 1, "two": 2}"#),
+            (r#"{"one": 0 + 1, true: 10 - 8}"#, r#"{"one": (0 + 1), true: (10 - 8)}"#),
+            ("fn() { {1: 2} }", "fn() {...}"),
+            ("if (x) { {1: 2}[1] }", "if x { ({1: 2}[1]) }"),
+        ];
+        test_runner(&test_case);
+    }
 }
@@ -1,16 +1,19 @@
 use crate::token::Token;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum Precedence {
     LOWEST,
-    ASSIGN,      // =
+    ASSIGN,      // '=' or '+=', '-=', '*=', '/=', '%='
+    OR,          // '||'
+    AND,         // '&&'
     EQUALS,      // '==' or '!='
-    LOGICAL,     // '&&' or '||'
     LESSGREATER, // '>' or '<'
+    RANGE,       // '..' or '..='
     SUM,         // '+' or '-'
-    PRODUCT,     // '*' or '/'
+    PRODUCT,     // '*', '/' or '%'
     PREFIX,      // '-x' or '!x'
-    CALL,        // 'myFunc(x)'
+    CALL,        // 'myFunc(x)' or 'x++'
     INDEX,       // 'myArray[0]'
 }
 
@@ -18,11 +21,18 @@ pub fn token_to_precedence(token: &Token) -> Precedence {
     match token {
         Token::LT | Token::GT => Precedence::LESSGREATER,
         Token::EQ | Token::NOT_EQ => Precedence::EQUALS,
-        Token::ASSIGN => Precedence::ASSIGN,
+        Token::ASSIGN
+        | Token::PLUS_ASSIGN
+        | Token::MINUS_ASSIGN
+        | Token::ASTERISK_ASSIGN
+        | Token::SLASH_ASSIGN
+        | Token::PERCENT_ASSIGN => Precedence::ASSIGN,
+        Token::DOTDOT | Token::DOTDOT_EQ => Precedence::RANGE,
         Token::PLUS | Token::MINUS => Precedence::SUM,
-        Token::SLASH | Token::ASTERISK => Precedence::PRODUCT,
-        Token::AND | Token::OR => Precedence::LOGICAL,
-        Token::LPAREN => Precedence::CALL,
+        Token::SLASH | Token::ASTERISK | Token::PERCENT => Precedence::PRODUCT,
+        Token::OR => Precedence::OR,
+        Token::AND => Precedence::AND,
+        Token::LPAREN | Token::INCREMENT | Token::DECREMENT => Precedence::CALL,
         Token::LBRACKET => Precedence::INDEX,
         _ => Precedence::LOWEST,
     }
@@ -1,18 +1,37 @@
 use std::{
     cell::RefCell,
-    io::{self, Write},
+    io::{self, IsTerminal, Write},
     rc::Rc,
 };
 
 use crate::{
+    compiler::Compiler,
+    diagnostics::{render_all, ColorMode, Diagnostic},
     evaluator::{environment::Env, eval},
+    gc,
     parser::parser_test::parse,
+    vm::Vm,
 };
 
 const PROMPT: &str = ">> ";
+const FILE_NAME: &str = "<repl>";
 
-pub fn start() {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    Evaluator,
+    Vm,
+}
+
+pub fn start(backend: Backend) {
     let env: Env = Rc::new(RefCell::new(Default::default()));
+    let mut compiler = Compiler::default();
+    let mut vm = Vm::default();
+
+    let mode = if io::stderr().is_terminal() {
+        ColorMode::Ansi
+    } else {
+        ColorMode::Plain
+    };
 
     println!("Limoo 🍋  v0.0.1 repl!");
 
@@ -25,15 +44,27 @@ pub fn start() {
         io::stdin().read_line(&mut input).expect("Failed to read line");
 
         match parse(&input) {
-            Ok(node) => match eval(node, &Rc::clone(&env)) {
-                Ok(value) => println!("{}", value),
-                Err(err) => eprintln!("{}", err),
-            },
-            Err(errors) => {
-                for error in errors {
-                    eprintln!("{}", error);
+            Ok(node) => {
+                let result = match backend {
+                    Backend::Evaluator => eval(node, &Rc::clone(&env)).map_err(|err| Diagnostic::from(&err)),
+                    Backend::Vm => compiler
+                        .compile(node)
+                        .map_err(|err| Diagnostic::from(&err))
+                        .and_then(|bytecode| vm.run(bytecode).map_err(|err| Diagnostic::from(&err))),
+                };
+
+                match result {
+                    Ok(value) => println!("{}", value),
+                    Err(diagnostic) => eprint!("{}", diagnostic.render(FILE_NAME, &input, mode)),
                 }
             }
+            Err(errors) => {
+                let diagnostics = errors.iter().map(Diagnostic::from).collect::<Vec<Diagnostic>>();
+                eprint!("{}", render_all(&diagnostics, FILE_NAME, &input, mode));
+            }
         }
+
+        // Like the WASM entry points, free the cycles left unreachable by each line.
+        gc::collect();
     }
 }
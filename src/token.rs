@@ -1,33 +1,92 @@
 use core::fmt;
 
+use crate::bigint::BigInt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Span { start, end }
+    }
+
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start, other.end)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.start.line, self.start.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+// A piece of an interpolated string: literal text, or the source of an embedded expression
+// together with the position it starts at.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplatePart {
+    Text(String),
+    Code(String, Position),
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
-    ILLEGAL,
+    ILLEGAL(char), // unexpected character
+    ERROR(String), // malformed literal, carries the lexer's message
     EOF,
 
     // Identifiers & literals
-    IDENT(String),  // add, foobar, x, y, ...
-    INT(i32),       // 1343456
-    BOOLEAN(bool),  // true, false
-    STRING(String), // "foobar"
+    IDENT(String),               // add, foobar, x, y, ...
+    INT(i64),                    // 1343456
+    BIGINT(BigInt),              // 99999999999999999999
+    FLOAT(f64),                  // 3.14, 1e-3
+    BOOLEAN(bool),               // true, false
+    STRING(String),              // "foobar"
+    TEMPLATE(Vec<TemplatePart>), // "hello ${name}"
 
     // Operators
-    ASSIGN,   // "="
-    PLUS,     // "+"
-    MINUS,    // "-"
-    BANG,     // "!"
-    ASTERISK, // "*"
-    SLASH,    // "/"
-    LT,       // "<"
-    GT,       // ">"
-    EQ,       // "=="
-    NOT_EQ,   // "!="
-    AND,      // "&&"
-    OR,       // "||"
+    ASSIGN,          // "="
+    PLUS_ASSIGN,     // "+="
+    MINUS_ASSIGN,    // "-="
+    ASTERISK_ASSIGN, // "*="
+    SLASH_ASSIGN,    // "/="
+    PERCENT_ASSIGN,  // "%="
+    INCREMENT,       // "++"
+    DECREMENT,       // "--"
+    PLUS,            // "+"
+    MINUS,           // "-"
+    BANG,            // "!"
+    ASTERISK,        // "*"
+    SLASH,           // "/"
+    PERCENT,         // "%"
+    LT,              // "<"
+    GT,              // ">"
+    EQ,              // "=="
+    NOT_EQ,          // "!="
+    AND,             // "&&"
+    OR,              // "||"
+    DOTDOT,          // ".."
+    DOTDOT_EQ,       // "..="
 
     // Delimiters
     COMMA,     // ","
+    COLON,     // ":"
     SEMICOLON, // ";"
     LPAREN,    // "("
     RPAREN,    // ")"
@@ -41,8 +100,30 @@ pub enum Token {
     LET,      // "LET"
     IF,       // "IF"
     WHILE,    // "WHILE"
+    FOR,      // "FOR"
+    IN,       // "IN"
     ELSE,     // "ELSE"
     RETURN,   // "RETURN"
+    BREAK,    // "BREAK"
+    CONTINUE, // "CONTINUE"
+    THROW,    // "THROW"
+    TRY,      // "TRY"
+    CATCH,    // "CATCH"
+    FINALLY,  // "FINALLY"
+}
+
+impl Token {
+    // The arithmetic operator applied by a compound assignment or an increment/decrement.
+    pub fn binary_operator(&self) -> Option<Token> {
+        match self {
+            Token::PLUS_ASSIGN | Token::INCREMENT => Some(Token::PLUS),
+            Token::MINUS_ASSIGN | Token::DECREMENT => Some(Token::MINUS),
+            Token::ASTERISK_ASSIGN => Some(Token::ASTERISK),
+            Token::SLASH_ASSIGN => Some(Token::SLASH),
+            Token::PERCENT_ASSIGN => Some(Token::PERCENT),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Token {
@@ -50,21 +131,34 @@ impl fmt::Display for Token {
         match self {
             Token::IDENT(id) => write!(f, "{}", id),
             Token::INT(i) => write!(f, "{}", i),
+            Token::BIGINT(i) => write!(f, "{}", i),
+            Token::FLOAT(float) => write!(f, "{:?}", float),
             Token::BOOLEAN(b) => write!(f, "{}", b),
             Token::STRING(s) => write!(f, "{}", s),
             Token::ASSIGN => write!(f, "="),
+            Token::PLUS_ASSIGN => write!(f, "+="),
+            Token::MINUS_ASSIGN => write!(f, "-="),
+            Token::ASTERISK_ASSIGN => write!(f, "*="),
+            Token::SLASH_ASSIGN => write!(f, "/="),
+            Token::PERCENT_ASSIGN => write!(f, "%="),
+            Token::INCREMENT => write!(f, "++"),
+            Token::DECREMENT => write!(f, "--"),
             Token::PLUS => write!(f, "+"),
             Token::MINUS => write!(f, "-"),
             Token::BANG => write!(f, "!"),
             Token::ASTERISK => write!(f, "*"),
             Token::SLASH => write!(f, "/"),
+            Token::PERCENT => write!(f, "%"),
             Token::LT => write!(f, "<"),
             Token::GT => write!(f, ">"),
             Token::EQ => write!(f, "=="),
             Token::NOT_EQ => write!(f, "!="),
             Token::AND => write!(f, "&&"),
             Token::OR => write!(f, "||"),
+            Token::DOTDOT => write!(f, ".."),
+            Token::DOTDOT_EQ => write!(f, "..="),
             Token::COMMA => write!(f, ","),
+            Token::COLON => write!(f, ":"),
             Token::SEMICOLON => write!(f, ";"),
             Token::LPAREN => write!(f, "("),
             Token::RPAREN => write!(f, ")"),
@@ -74,8 +168,16 @@ impl fmt::Display for Token {
             Token::LET => write!(f, "let"),
             Token::IF => write!(f, "if"),
             Token::WHILE => write!(f, "while"),
+            Token::FOR => write!(f, "for"),
+            Token::IN => write!(f, "in"),
             Token::ELSE => write!(f, "else"),
             Token::RETURN => write!(f, "return"),
+            Token::BREAK => write!(f, "break"),
+            Token::CONTINUE => write!(f, "continue"),
+            Token::THROW => write!(f, "throw"),
+            Token::TRY => write!(f, "try"),
+            Token::CATCH => write!(f, "catch"),
+            Token::FINALLY => write!(f, "finally"),
             token => write!(f, "{:?}", token),
         }
     }
@@ -0,0 +1,57 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{compiler::instruction::Instruction, evaluator::error::TailCalls, object::Object, token::Span};
+
+use super::{Binding, Closure};
+
+#[derive(Debug)]
+pub struct Frame {
+    pub closure: Rc<Closure>,
+    pub ip: usize,
+    pub base_pointer: usize,
+    pub call_site: Option<Span>,
+    pub depth: usize,
+    pub replaced: TailCalls,
+    pub locals: Vec<Binding>,
+    pub loop_heights: Vec<usize>,
+    pub handlers: Vec<Handler>,
+}
+
+// Where to resume when an error reaches an active `try`, and the state to restore first.
+#[derive(Debug)]
+pub struct Handler {
+    pub target: usize,
+    pub stack_height: usize,
+    pub loop_depth: usize,
+}
+
+impl Frame {
+    pub fn new(closure: Rc<Closure>, base_pointer: usize, call_site: Option<Span>, depth: usize) -> Self {
+        let locals = (0..closure.function.num_locals)
+            .map(|_| Rc::new(RefCell::new(Rc::new(Object::Null))))
+            .collect();
+
+        Frame {
+            closure,
+            ip: 0,
+            base_pointer,
+            call_site,
+            depth,
+            replaced: TailCalls::default(),
+            locals,
+            loop_heights: vec![],
+            handlers: vec![],
+        }
+    }
+
+    pub fn next_instruction(&mut self) -> Instruction {
+        let instruction = self.closure.function.instructions[self.ip];
+        self.ip += 1;
+        instruction
+    }
+
+    pub fn current_span(&self) -> Option<Span> {
+        let position = self.ip.checked_sub(1)?;
+        self.closure.function.spans.get(position).copied()
+    }
+}
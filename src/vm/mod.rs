@@ -0,0 +1,497 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use crate::{
+    compiler::{instruction::Instruction, symbol_table::SymbolScope, Bytecode, CompiledFunction},
+    diagnostics::did_you_mean,
+    evaluator::{
+        assign_index,
+        builtins::Builtin,
+        check_call_depth,
+        error::{EvaluatorError, StackFrame},
+        eval_hash_literal, eval_index_expression, eval_infix_expression, eval_interpolation, eval_prefix_expression,
+        eval_slice_expression, is_truthy, iteration_items, EvaluatorResult,
+    },
+    gc,
+    object::Object,
+};
+
+use self::frame::{Frame, Handler};
+
+mod frame;
+mod vm_test;
+
+pub type Binding = Rc<RefCell<Rc<Object>>>;
+
+#[derive(Debug, PartialEq)]
+pub struct Closure {
+    pub function: Rc<CompiledFunction>,
+    pub free: Vec<Binding>,
+}
+
+impl Closure {
+    pub fn new(function: Rc<CompiledFunction>, free: Vec<Binding>) -> Self {
+        gc::closure_created();
+        Closure { function, free }
+    }
+}
+
+impl Drop for Closure {
+    fn drop(&mut self) {
+        gc::closure_dropped();
+    }
+}
+
+impl fmt::Display for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.function)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Vm {
+    stack: Vec<Rc<Object>>,
+    globals: Vec<Option<Rc<Object>>>,
+    frames: Vec<Frame>,
+}
+
+impl Vm {
+    pub fn run(&mut self, bytecode: Bytecode) -> EvaluatorResult {
+        let main_function = CompiledFunction {
+            instructions: bytecode.instructions,
+            spans: bytecode.spans,
+            num_locals: bytecode.num_locals,
+            parameters: vec![],
+            captures: vec![],
+            name: None,
+        };
+        let main_closure = Closure::new(Rc::new(main_function), vec![]);
+
+        self.stack.clear();
+        self.frames = vec![Frame::new(Rc::new(main_closure), 0, None, 0)];
+
+        let result = self.execute(&bytecode.constants, &bytecode.global_names);
+
+        if result.is_err() {
+            self.stack.clear();
+            self.frames.clear();
+        }
+        result
+    }
+
+    fn execute(&mut self, constants: &[Rc<Object>], global_names: &[String]) -> EvaluatorResult {
+        loop {
+            let err = match self.execute_instructions(constants, global_names) {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+
+            let err = match self.frames.last().and_then(|frame| frame.current_span()) {
+                Some(span) => err.with_span(span),
+                None => err,
+            };
+            self.catch(err)?;
+        }
+    }
+
+    // Unwinds to the innermost active handler, in this frame or a calling one, and resumes there
+    // with the error object on the stack. Every call unwound is recorded on the error.
+    fn catch(&mut self, mut err: EvaluatorError) -> Result<(), EvaluatorError> {
+        let handler_depth = self.frames.iter().rposition(|frame| !frame.handlers.is_empty());
+
+        for frame in self.frames[handler_depth.unwrap_or(0) + 1..].iter().rev() {
+            err = err.with_frame(StackFrame::new(frame.closure.function.name.as_deref(), frame.call_site));
+            err = frame.replaced.trace(err);
+        }
+
+        let depth = match handler_depth {
+            Some(depth) => depth,
+            None => return Err(err),
+        };
+        self.frames.truncate(depth + 1);
+
+        let frame = self.current_frame();
+        let handler = frame.handlers.pop().expect("frame has an active handler");
+        frame.ip = handler.target;
+        frame.loop_heights.truncate(handler.loop_depth);
+
+        self.stack.truncate(handler.stack_height);
+        self.push(Rc::new(Object::Error(Rc::new(err))));
+        Ok(())
+    }
+
+    fn execute_instructions(&mut self, constants: &[Rc<Object>], global_names: &[String]) -> EvaluatorResult {
+        loop {
+            let instruction = self.current_frame().next_instruction();
+
+            match instruction {
+                Instruction::Constant(index) => self.push(Rc::clone(&constants[index])),
+                Instruction::Null => self.push(Rc::new(Object::Null)),
+                Instruction::True => self.push(Rc::new(Object::Boolean(true))),
+                Instruction::False => self.push(Rc::new(Object::Boolean(false))),
+                Instruction::Pop => {
+                    self.pop();
+                }
+                Instruction::Dup => {
+                    let value = Rc::clone(self.peek());
+                    self.push(value);
+                }
+                Instruction::Add
+                | Instruction::Sub
+                | Instruction::Mul
+                | Instruction::Div
+                | Instruction::Mod
+                | Instruction::Equal
+                | Instruction::NotEqual
+                | Instruction::LessThan
+                | Instruction::GreaterThan
+                | Instruction::Range
+                | Instruction::RangeInclusive => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let operator = instruction.operator().unwrap();
+                    self.push(eval_infix_expression(&left, &operator, &right)?);
+                }
+                Instruction::Minus | Instruction::Bang => {
+                    let right = self.pop();
+                    let operator = instruction.operator().unwrap();
+                    self.push(eval_prefix_expression(&operator, &right)?);
+                }
+                Instruction::Jump(position) => self.current_frame().ip = position,
+                Instruction::JumpNotTruthy(position) => {
+                    let condition = self.pop();
+                    if !is_truthy(&condition) {
+                        self.current_frame().ip = position;
+                    }
+                }
+                Instruction::JumpNotTruthyOrPop(position) => {
+                    if is_truthy(self.peek()) {
+                        self.pop();
+                    } else {
+                        self.current_frame().ip = position;
+                    }
+                }
+                Instruction::JumpTruthyOrPop(position) => {
+                    if is_truthy(self.peek()) {
+                        self.current_frame().ip = position;
+                    } else {
+                        self.pop();
+                    }
+                }
+                Instruction::EnterLoop => {
+                    let height = self.stack.len();
+                    self.current_frame().loop_heights.push(height);
+                }
+                Instruction::ExitLoop => {
+                    self.current_frame().loop_heights.pop();
+                }
+                Instruction::Break(position) | Instruction::Continue(position) => {
+                    let frame = self.current_frame();
+                    let height = *frame.loop_heights.last().expect("loop control outside of a loop");
+                    frame.ip = position;
+                    self.stack.truncate(height);
+                }
+                Instruction::Iterate => {
+                    let iterable = self.pop();
+                    match *iterable {
+                        Object::Range(_) => self.push(iterable),
+                        _ => self.push(Rc::new(Object::Array(iteration_items(&iterable)?))),
+                    }
+                    self.push(Rc::new(Object::Integer(0)));
+                }
+                Instruction::IterateNext(position) => match self.next_item() {
+                    Some(item) => self.push(item),
+                    None => self.current_frame().ip = position,
+                },
+                Instruction::GetGlobal(index) => match self.globals.get(index).cloned().flatten() {
+                    Some(value) => self.push(value),
+                    None => {
+                        return Err(self.error_undeclared(
+                            format!("Identifier not found: {}", global_names[index]),
+                            &global_names[index],
+                            global_names,
+                        ))
+                    }
+                },
+                Instruction::SetGlobal(index) => {
+                    let value = Rc::clone(self.peek());
+                    if index >= self.globals.len() {
+                        self.globals.resize(index + 1, None);
+                    }
+                    self.globals[index] = Some(value);
+                }
+                Instruction::AssignGlobal(index) => {
+                    let value = Rc::clone(self.peek());
+                    match self.globals.get_mut(index) {
+                        Some(Some(global)) => *global = value,
+                        _ => {
+                            return Err(self.error_undeclared(
+                                format!("Cannot assign to undeclared variable: {}", global_names[index]),
+                                &global_names[index],
+                                global_names,
+                            ))
+                        }
+                    }
+                }
+                Instruction::GetLocal(index) => {
+                    let value = Rc::clone(&self.current_frame().locals[index].borrow());
+                    self.push(value);
+                }
+                Instruction::SetLocal(index) => {
+                    let value = Rc::clone(self.peek());
+                    *self.current_frame().locals[index].borrow_mut() = value;
+                }
+                Instruction::NewLocal(index) => {
+                    self.current_frame().locals[index] = Rc::new(RefCell::new(Rc::new(Object::Null)));
+                }
+                Instruction::GetFree(index) => {
+                    let value = Rc::clone(&self.current_frame().closure.free[index].borrow());
+                    self.push(value);
+                }
+                Instruction::SetFree(index) => {
+                    let value = Rc::clone(self.peek());
+                    *self.current_frame().closure.free[index].borrow_mut() = value;
+                }
+                Instruction::GetBuiltin(builtin) => self.push(Rc::new(Object::Builtin(builtin))),
+                Instruction::Array(length) => {
+                    let elements = self.stack.split_off(self.stack.len() - length);
+                    self.push(Rc::new(Object::Array(elements)));
+                }
+                Instruction::Hash(length) => {
+                    let mut elements = self.stack.split_off(self.stack.len() - length * 2).into_iter();
+                    let mut pairs = Vec::with_capacity(length);
+
+                    while let (Some(key), Some(value)) = (elements.next(), elements.next()) {
+                        pairs.push((key, value));
+                    }
+
+                    self.push(eval_hash_literal(pairs)?);
+                }
+                Instruction::Interpolate(length) => {
+                    let parts = self.stack.split_off(self.stack.len() - length);
+                    self.push(eval_interpolation(&parts));
+                }
+                Instruction::Index => {
+                    let index = self.pop();
+                    let left = self.pop();
+                    self.push(eval_index_expression(&left, &index)?);
+                }
+                Instruction::IndexKeep => {
+                    let index = &self.stack[self.stack.len() - 1];
+                    let container = &self.stack[self.stack.len() - 2];
+                    let value = eval_index_expression(container, index)?;
+                    self.push(value);
+                }
+                Instruction::SetIndexGlobal(index, depth) => {
+                    let (path, value, result) = self.pop_index_assignment(depth);
+                    let global = self.globals[index].get_or_insert_with(|| Rc::new(Object::Null));
+                    assign_index(global, &path, value)?;
+                    self.push(result);
+                }
+                Instruction::SetIndexLocal(index, depth) => {
+                    let (path, value, result) = self.pop_index_assignment(depth);
+                    let binding = Rc::clone(&self.current_frame().locals[index]);
+                    assign_index(&mut binding.borrow_mut(), &path, value)?;
+                    self.push(result);
+                }
+                Instruction::SetIndexFree(index, depth) => {
+                    let (path, value, result) = self.pop_index_assignment(depth);
+                    let binding = Rc::clone(&self.current_frame().closure.free[index]);
+                    assign_index(&mut binding.borrow_mut(), &path, value)?;
+                    self.push(result);
+                }
+                Instruction::Slice => {
+                    let end = self.pop();
+                    let start = self.pop();
+                    let left = self.pop();
+                    self.push(eval_slice_expression(&left, &start, &end)?);
+                }
+                Instruction::Closure(index) => {
+                    let closure = Rc::new(self.new_closure(&constants[index])?);
+                    if !closure.free.is_empty() {
+                        gc::track_closure(&closure);
+                    }
+                    self.push(Rc::new(Object::Closure(closure)));
+                }
+                Instruction::Call(num_args) => self.call_function(num_args)?,
+                Instruction::TailCall(num_args) => self.tail_call(num_args)?,
+                Instruction::ReturnValue => {
+                    let value = self.pop();
+                    let frame = self.frames.pop().unwrap();
+
+                    if self.frames.is_empty() {
+                        return Ok(value);
+                    }
+
+                    self.stack.truncate(frame.base_pointer);
+                    self.push(value);
+                }
+                Instruction::PushHandler(target) => {
+                    let stack_height = self.stack.len();
+                    let frame = self.current_frame();
+                    let loop_depth = frame.loop_heights.len();
+                    frame.handlers.push(Handler {
+                        target,
+                        stack_height,
+                        loop_depth,
+                    });
+                }
+                Instruction::PopHandler => {
+                    self.current_frame().handlers.pop();
+                }
+                Instruction::Throw => return Err(EvaluatorError::thrown(self.pop())),
+            }
+        }
+    }
+
+    fn new_closure(&mut self, constant: &Rc<Object>) -> Result<Closure, EvaluatorError> {
+        let function = match &**constant {
+            Object::CompiledFunction(function) => Rc::clone(function),
+            object => return Err(EvaluatorError::new(format!("Not a function: {}", object))),
+        };
+
+        let frame = self.current_frame();
+        let free = function
+            .captures
+            .iter()
+            .map(|symbol| match symbol.scope {
+                SymbolScope::Local => Rc::clone(&frame.locals[symbol.index]),
+                SymbolScope::Free => Rc::clone(&frame.closure.free[symbol.index]),
+                SymbolScope::Global => unreachable!("global bindings are never captured"),
+            })
+            .collect();
+
+        Ok(Closure::new(function, free))
+    }
+
+    fn call_function(&mut self, num_args: usize) -> Result<(), EvaluatorError> {
+        let base_pointer = self.stack.len() - 1 - num_args;
+        let callee = Rc::clone(&self.stack[base_pointer]);
+
+        match &*callee {
+            Object::Closure(closure) => {
+                check_arguments(closure, num_args)?;
+                let depth = self.current_frame().depth;
+                check_call_depth(depth)?;
+
+                let call_site = self.frames.last().and_then(|frame| frame.current_span());
+                let frame = Frame::new(Rc::clone(closure), base_pointer, call_site, depth + 1);
+                for (local, arg) in frame.locals.iter().zip(self.stack.drain(base_pointer + 1..)) {
+                    *local.borrow_mut() = arg;
+                }
+
+                self.frames.push(frame);
+                Ok(())
+            }
+            Object::Builtin(builtin) => {
+                let args = self.stack.split_off(base_pointer + 1);
+                let result = builtin.apply(&args)?;
+
+                self.stack.truncate(base_pointer);
+                self.push(result);
+                Ok(())
+            }
+            _ => Err(EvaluatorError::new(format!("Not a function: {}", callee))),
+        }
+    }
+
+    // Replaces the current frame with the callee's, moving the callee down to where the current
+    // function sits on the stack. The replaced call takes no room, so it doesn't count towards the
+    // call depth, but it is kept for stack traces.
+    fn tail_call(&mut self, num_args: usize) -> Result<(), EvaluatorError> {
+        let position = self.stack.len() - 1 - num_args;
+        let closure = match &*self.stack[position] {
+            Object::Closure(closure) => Rc::clone(closure),
+            _ => return self.call_function(num_args),
+        };
+        check_arguments(&closure, num_args)?;
+
+        let call_site = self.frames.last().and_then(|frame| frame.current_span());
+        let current = self.current_frame();
+        let mut frame = Frame::new(closure, current.base_pointer, call_site, current.depth);
+        frame.replaced = std::mem::take(&mut current.replaced);
+        frame
+            .replaced
+            .record(current.closure.function.name.as_deref(), current.call_site);
+
+        let base_pointer = frame.base_pointer;
+        for (local, arg) in frame.locals.iter().zip(self.stack.drain(position + 1..)) {
+            *local.borrow_mut() = arg;
+        }
+
+        let callee = self.pop();
+        self.stack.truncate(base_pointer);
+        self.push(callee);
+        *self.current_frame() = frame;
+        Ok(())
+    }
+
+    // Takes the indexes, the value and the result of an index assignment off the stack. The
+    // containers read on the way to the target are dropped, leaving the variable with the only
+    // reference to them.
+    fn pop_index_assignment(&mut self, depth: usize) -> (Vec<Rc<Object>>, Rc<Object>, Rc<Object>) {
+        let value = self.pop();
+        let result = self.pop();
+        let target = self.stack.split_off(self.stack.len() - depth * 2);
+        let path = target.into_iter().skip(1).step_by(2).collect();
+
+        (path, value, result)
+    }
+
+    fn error_undeclared(&self, message: String, identifier: &str, global_names: &[String]) -> EvaluatorError {
+        let candidates = global_names
+            .iter()
+            .zip(&self.globals)
+            .filter(|(_, value)| value.is_some())
+            .map(|(name, _)| name.as_str())
+            .chain(Builtin::ALL.iter().map(|builtin| builtin.name()))
+            .collect::<Vec<&str>>();
+
+        EvaluatorError::new(message).with_help(did_you_mean(identifier, candidates))
+    }
+
+    // The iteration state is the item array (or range) and the next index, on top of the stack.
+    fn next_item(&mut self) -> Option<Rc<Object>> {
+        let length = self.stack.len();
+        let index = match *self.stack[length - 1] {
+            Object::Integer(index) => index as usize,
+            _ => unreachable!("iteration index is always an integer"),
+        };
+        let item = match &*self.stack[length - 2] {
+            Object::Array(items) => items.get(index).cloned(),
+            Object::Range(range) => range.get(index as i128).map(|i| Rc::new(Object::Integer(i))),
+            _ => unreachable!("iteration items are always an array or a range"),
+        };
+
+        if item.is_some() {
+            self.stack[length - 1] = Rc::new(Object::Integer(index as i64 + 1));
+        }
+        item
+    }
+
+    fn current_frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().expect("VM has no active frame")
+    }
+
+    fn push(&mut self, object: Rc<Object>) {
+        self.stack.push(object);
+    }
+
+    fn pop(&mut self) -> Rc<Object> {
+        self.stack.pop().expect("VM stack underflow")
+    }
+
+    fn peek(&self) -> &Rc<Object> {
+        self.stack.last().expect("VM stack underflow")
+    }
+}
+
+fn check_arguments(closure: &Closure, num_args: usize) -> Result<(), EvaluatorError> {
+    let num_params = closure.function.parameters.len();
+    if num_params != num_args {
+        return Err(EvaluatorError::new(format!(
+            "Expected {} arguments but got {}",
+            num_params, num_args
+        )));
+    }
+    Ok(())
+}
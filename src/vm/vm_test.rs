@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod vm_test {
+    use crate::{compiler::Compiler, parser::parser_test::parse, vm::Vm};
+
+    fn test_runner(test_case: &[(&str, &str)]) {
+        let mut compiler = Compiler::default();
+        let mut vm = Vm::default();
+
+        for (input, expected) in test_case {
+            let node = parse(input).expect("Parsing Error");
+            let bytecode = compiler.compile(node).expect("Compiler Error");
+
+            match vm.run(bytecode) {
+                Ok(actual) => assert_eq!(expected, &format!("{}", actual), "input: {}", input),
+                Err(err) => assert_eq!(expected, &format!("{}", err), "input: {}", input),
+            }
+        }
+    }
+
+    #[test]
+    fn test_globals_persist_between_runs() {
+        let tests = [
+            ("let a = 1;", "1"),
+            ("let add = fn(x) { a + x };", "fn(x) {...}"),
+            ("a = 10; add(5)", "15"),
+            ("b", "Identifier not found: b"),
+            ("let b = 2; b", "2"),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_forward_reference_to_global() {
+        let tests = [(
+            "let isEven = fn(n) { if (n == 0) { true } else { isOdd(n - 1) } };
+             let isOdd = fn(n) { if (n == 0) { false } else { isEven(n - 1) } };
+             isEven(10)",
+            "true",
+        )];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_closures_share_captured_bindings() {
+        let tests = [
+            (
+                "let counter = fn() {
+                   let count = 0;
+                   let increment = fn() { count = count + 1 };
+                   increment();
+                   increment();
+                   count
+                 };
+                 counter()",
+                "2",
+            ),
+            (
+                "let outer = fn() {
+                   let x = 1;
+                   let middle = fn() { fn() { x } };
+                   x = 5;
+                   middle()()
+                 };
+                 outer()",
+                "5",
+            ),
+        ];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_recursive_local_function() {
+        let tests = [(
+            "let wrapper = fn() {
+               let countDown = fn(x) { if (x == 0) { return 0; } countDown(x - 1) };
+               countDown(3)
+             };
+             wrapper()",
+            "0",
+        )];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_fibonacci() {
+        let tests = [(
+            "let fibonacci = fn(n) {
+               if (n < 2) { return n; }
+               fibonacci(n - 1) + fibonacci(n - 2)
+             };
+             fibonacci(20)",
+            "6765",
+        )];
+        test_runner(&tests);
+    }
+
+    #[test]
+    fn test_calling_errors() {
+        let tests = [
+            ("fn(a) { a }()", "Expected 1 arguments but got 0"),
+            ("1()", "Not a function: 1"),
+            ("len(1)", "Argument to `len` not supported, got 1"),
+            ("1 + true", "Mismatch type: 1 + true"),
+        ];
+        test_runner(&tests);
+    }
+}
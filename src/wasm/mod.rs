@@ -1,21 +1,61 @@
 use wasm_bindgen::prelude::*;
 
+use crate::compiler::Compiler;
+use crate::diagnostics::{render_all, ColorMode, Diagnostic};
 use crate::evaluator::environment::*;
 use crate::evaluator::*;
+use crate::gc;
+use crate::parser::error::ParserError;
 use crate::parser::parser_test::parse;
+use crate::vm::Vm;
 
 use std::cell::RefCell;
 use std::rc::Rc;
 
+mod wasm_test;
+
+const FILE_NAME: &str = "<playground>";
+
 #[wasm_bindgen]
 pub fn limoo_eval(input: &str) -> String {
     let env: Rc<RefCell<Environment>> = Rc::new(RefCell::new(Default::default()));
 
-    match parse(&input) {
+    let output = match parse(input) {
         Ok(node) => match eval(node, &Rc::clone(&env)) {
             Ok(evaluated) => evaluated.to_string(),
-            Err(err) => err.to_string(),
+            Err(err) => Diagnostic::from(&err).render(FILE_NAME, input, ColorMode::Plain),
         },
-        Err(errors) => errors.into_iter().map(|e| format!("{}\n", e)).collect::<String>(),
-    }
+        Err(errors) => render_parser_errors(&errors, input),
+    };
+
+    // Functions defined by the program keep its environment alive through cycles.
+    drop(env);
+    gc::collect();
+    output
+}
+
+#[wasm_bindgen]
+pub fn limoo_eval_vm(input: &str) -> String {
+    let mut compiler = Compiler::default();
+    let mut vm = Vm::default();
+
+    let output = match parse(input) {
+        Ok(node) => match compiler.compile(node) {
+            Ok(bytecode) => match vm.run(bytecode) {
+                Ok(evaluated) => evaluated.to_string(),
+                Err(err) => Diagnostic::from(&err).render(FILE_NAME, input, ColorMode::Plain),
+            },
+            Err(err) => Diagnostic::from(&err).render(FILE_NAME, input, ColorMode::Plain),
+        },
+        Err(errors) => render_parser_errors(&errors, input),
+    };
+
+    drop(vm);
+    gc::collect();
+    output
+}
+
+fn render_parser_errors(errors: &[ParserError], input: &str) -> String {
+    let diagnostics = errors.iter().map(Diagnostic::from).collect::<Vec<Diagnostic>>();
+    render_all(&diagnostics, FILE_NAME, input, ColorMode::Plain)
 }
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod wasm_test {
+    // The same tests run on the wasm32 target through `wasm-pack test --node`, with its own limits.
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use crate::{
+        evaluator::DEFAULT_MAX_CALL_DEPTH,
+        wasm::{limoo_eval, limoo_eval_vm},
+    };
+
+    #[test]
+    fn test_runaway_recursion() {
+        let input = "let f = fn(n) { 1 + f(n + 1) };\nf(0)";
+
+        for eval in [limoo_eval, limoo_eval_vm] {
+            let expected = [
+                format!(
+                    "error: Maximum recursion depth exceeded: {} calls deep",
+                    DEFAULT_MAX_CALL_DEPTH
+                ),
+                " --> <playground>:1:21".to_string(),
+                "  |".to_string(),
+                "1 | let f = fn(n) { 1 + f(n + 1) };".to_string(),
+                "  |                     ^^^^^^^^".to_string(),
+                format!("  = note: in f, called at 1:21 ({} times)", DEFAULT_MAX_CALL_DEPTH - 1),
+                "  = note: in f, called at 2:1".to_string(),
+                "".to_string(),
+            ]
+            .join("\n");
+
+            assert_eq!(eval(input), expected);
+        }
+    }
+
+    #[test]
+    fn test_deep_recursion_within_limit() {
+        let depth = DEFAULT_MAX_CALL_DEPTH - 1;
+        let input = format!(
+            "let g = fn(n) {{ if (n == 0) {{ 0 }} else {{ 1 + g(n - 1) }} }}; g({})",
+            depth
+        );
+
+        assert_eq!(limoo_eval(&input), depth.to_string());
+        assert_eq!(limoo_eval_vm(&input), depth.to_string());
+    }
+
+    #[test]
+    fn test_recursion_through_try() {
+        let input = format!(
+            "let h = fn(n) {{ if (n == 0) {{ 0 }} else {{ try {{ 1 + h(n - 1) }} catch (e) {{ throw e; }} }} }}; h({})",
+            DEFAULT_MAX_CALL_DEPTH - 1
+        );
+
+        assert_eq!(limoo_eval(&input), (DEFAULT_MAX_CALL_DEPTH - 1).to_string());
+        assert_eq!(limoo_eval_vm(&input), (DEFAULT_MAX_CALL_DEPTH - 1).to_string());
+    }
+}